@@ -1,6 +1,7 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{spanned::Spanned, DataStruct, DeriveInput, Field, Fields, FieldsNamed, Token};
+use syn::spanned::Spanned;
+use syn::{DataStruct, DeriveInput, Field, Fields, FieldsNamed, Token};
 
 #[proc_macro_derive(Builder, attributes(builder))]
 pub fn derive(input: TokenStream) -> TokenStream {
@@ -8,93 +9,220 @@ pub fn derive(input: TokenStream) -> TokenStream {
     let name = &input.ident;
     let builder_name = quote::format_ident!("{}Builder", input.ident);
     let fields = get_all_fields(&input.data);
+    let struct_attrs = match get_struct_attrs(&input.attrs) {
+        Ok(attrs) => attrs,
+        Err(err) => return err.into_compile_error().into(),
+    };
 
     let mut builder_values: Vec<proc_macro2::TokenStream> = vec![];
     let mut ty_fields: Vec<proc_macro2::TokenStream> = vec![];
     let mut setters: Vec<proc_macro2::TokenStream> = vec![];
     let mut build_values: Vec<proc_macro2::TokenStream> = vec![];
+    let mut take_checks: Vec<proc_macro2::TokenStream> = vec![];
+    let mut take_values: Vec<proc_macro2::TokenStream> = vec![];
+    let mut merges: Vec<proc_macro2::TokenStream> = vec![];
 
     for e in fields.iter() {
         let ident = &e.ident;
         let ty = &e.ty;
-        let inner_option = get_inner("Option", ty);
-        let inner_vec = get_inner("Vec", ty);
-        let meta = get_meta(e);
+        let attrs = match get_field_attrs(e) {
+            Ok(attrs) => attrs,
+            Err(err) => return err.into_compile_error().into(),
+        };
+        let kind = match get_field_kind(ty, &attrs) {
+            Ok(kind) => kind,
+            Err(err) => return err.into_compile_error().into(),
+        };
 
-        builder_values.push(
-            // if have attr, it will be vec
-            if meta.is_some() {
-                quote! {
-                    #ident: vec![],
-                }
-            } else {
-                quote! {
-                    #ident: ::std::option::Option::None,
-                }
-            },
-        );
-        ty_fields.push(if inner_option.is_some() || meta.is_some() {
+        // required fields are wrapped in an extra Option, the rest are stored as is
+        builder_values.push(if let FieldKind::Required = kind {
             quote! {
-                #ident: #ty,
+                #ident: ::std::option::Option::None,
             }
         } else {
             quote! {
+                #ident: ::std::default::Default::default(),
+            }
+        });
+        // every builder field can be left out of a config file
+        let serde_default = if struct_attrs.serde {
+            quote! { #[serde(default)] }
+        } else {
+            quote! {}
+        };
+        ty_fields.push(if let FieldKind::Required = kind {
+            quote! {
+                #serde_default
                 #ident: ::std::option::Option<#ty>,
             }
+        } else {
+            quote! {
+                #serde_default
+                #ident: #ty,
+            }
         });
-        let mut ty_option = ty;
-        if let Some(inner) = inner_option {
-            ty_option = inner;
-        }
-        setters.push(if let Some(ref m) = meta {
-            match get_lit_from_meta(m) {
-                Ok(lit) => {
-                    let lit_ident = quote::format_ident!("{}", lit.value());
+        merges.push(
+            if let FieldKind::Repeated {
+                optional: false, ..
+            } = kind
+            {
+                quote! {
+                    if !other.#ident.is_empty() {
+                        self.#ident = other.#ident;
+                    }
+                }
+            } else {
+                // `optional` fields are stored as is and have to be an Option
+                // alias, which the error points at when they aren't
+                let is_some = quote::quote_spanned! {ty.span()=>
+                    ::std::option::Option::is_some(&other.#ident)
+                };
+                quote! {
+                    if #is_some {
+                        self.#ident = other.#ident;
+                    }
+                }
+            },
+        );
+        // custom setters are written by hand in a separate impl block
+        if !attrs.custom_setter {
+            setters.push(match kind {
+                FieldKind::Repeated { item, optional } => {
+                    let lit_ident = quote::format_ident!("{}", attrs.each.as_ref().unwrap().value());
+                    let extend_ident = quote::format_ident!("extend_{}", ident.as_ref().unwrap());
+                    let vec = if optional {
+                        quote! { self.#ident.get_or_insert_with(::std::vec::Vec::new) }
+                    } else {
+                        quote! { self.#ident }
+                    };
+                    let each_setter = if attrs.each_into {
+                        quote! {
+                            fn #lit_ident(&mut self, val: impl ::std::convert::Into<#item>) -> &mut Self {
+                                #vec.push(val.into());
+                                self
+                            }
+                        }
+                    } else {
+                        quote! {
+                            fn #lit_ident(&mut self, val: #item) -> &mut Self {
+                                #vec.push(val);
+                                self
+                            }
+                        }
+                    };
                     quote! {
-                        fn #lit_ident(&mut self, val: #inner_vec) -> &mut Self {
-                            self.#ident.push(val);
+                        #each_setter
+
+                        fn #extend_ident<I>(&mut self, vals: I) -> &mut Self
+                        where
+                            I: ::std::iter::IntoIterator,
+                            I::Item: ::std::convert::Into<#item>,
+                        {
+                            #vec.extend(vals.into_iter().map(::std::convert::Into::into));
                             self
                         }
                     }
                 }
-                Err(err) => {
-                    return syn::Error::new(meta.span(), err.to_string())
-                        .into_compile_error()
-                        .into();
-                }
-            }
-        } else {
-            quote! {
-                fn #ident(&mut self, #ident: #ty_option) -> &mut Self {
-                    self.#ident = Some(#ident);
-                    self
+                // the inner type of an aliased Option is unknown, so take it whole
+                FieldKind::Optional(None) => quote! {
+                    fn #ident(&mut self, #ident: #ty) -> &mut Self {
+                        self.#ident = #ident;
+                        self
+                    }
+                },
+                FieldKind::Optional(Some(_)) | FieldKind::Required => {
+                    let value = match kind {
+                        FieldKind::Optional(Some(inner)) => inner,
+                        _ => ty,
+                    };
+                    if is_smart_pointer(value) {
+                        quote! {
+                            fn #ident(&mut self, #ident: impl ::std::convert::Into<#value>) -> &mut Self {
+                                self.#ident = ::std::option::Option::Some(#ident.into());
+                                self
+                            }
+                        }
+                    } else {
+                        quote! {
+                            fn #ident(&mut self, #ident: #value) -> &mut Self {
+                                self.#ident = ::std::option::Option::Some(#ident);
+                                self
+                            }
+                        }
+                    }
                 }
-            }
-        });
-        build_values.push(if inner_option.is_some() || meta.is_some() {
-            quote! {
-                #ident: self.#ident.clone(),
-            }
-        } else {
+            });
+        }
+        if let FieldKind::Required = kind {
             let lit = syn::LitStr::new(
                 &format!("missing attribute {}", ident.clone().unwrap()),
                 proc_macro2::Span::call_site(),
             );
-            quote! {
+            build_values.push(quote! {
                 #ident: self.#ident.clone().ok_or(#lit)?,
-            }
-        })
+            });
+            // checked up front so a missing field doesn't drop the ones already taken
+            take_checks.push(quote! {
+                if self.#ident.is_none() {
+                    return ::std::result::Result::Err(#lit.into());
+                }
+            });
+            take_values.push(quote! {
+                #ident: self.#ident.take().ok_or(#lit)?,
+            });
+        } else {
+            build_values.push(quote! {
+                #ident: self.#ident.clone(),
+            });
+            take_values.push(quote! {
+                #ident: ::std::mem::take(&mut self.#ident),
+            });
+        }
     }
 
-    let build_method = quote! {
-        pub fn build(&mut self) -> ::std::result::Result<#name, ::std::boxed::Box<dyn ::std::error::Error>> {
-            Ok(#name {
-                #(#build_values)*
+    // `build_take` moves the values out and leaves the builder empty, so fields
+    // don't need to be `Clone`. `#[builder(take)]` makes `build` do the same.
+    let take_method = quote! {
+        pub fn build_take(&mut self) -> ::std::result::Result<#name, ::std::boxed::Box<dyn ::std::error::Error>> {
+            #(#take_checks)*
+            ::std::result::Result::Ok(#name {
+                #(#take_values)*
             })
         }
     };
+    let build_method = if struct_attrs.take {
+        quote! {
+            pub fn build(&mut self) -> ::std::result::Result<#name, ::std::boxed::Box<dyn ::std::error::Error>> {
+                self.build_take()
+            }
+        }
+    } else {
+        quote! {
+            pub fn build(&mut self) -> ::std::result::Result<#name, ::std::boxed::Box<dyn ::std::error::Error>> {
+                Ok(#name {
+                    #(#build_values)*
+                })
+            }
+        }
+    };
+
+    // layered configs: deserialize partial builders and merge them in order
+    let (serde_derive, merge_method) = if struct_attrs.serde {
+        (
+            quote! { #[derive(::serde::Deserialize)] },
+            quote! {
+                pub fn merge(&mut self, other: Self) -> &mut Self {
+                    #(#merges)*
+                    self
+                }
+            },
+        )
+    } else {
+        (quote! {}, quote! {})
+    };
 
     quote! {
+        #serde_derive
         pub struct #builder_name {
             #(#ty_fields)*
         }
@@ -102,11 +230,13 @@ pub fn derive(input: TokenStream) -> TokenStream {
         impl #builder_name {
             #(#setters)*
             #build_method
+            #take_method
+            #merge_method
         }
 
-        impl Command {
+        impl #name {
             pub fn builder() -> #builder_name {
-                CommandBuilder {
+                #builder_name {
                     #(#builder_values)*
                 }
             }
@@ -127,54 +257,214 @@ fn get_all_fields(data: &syn::Data) -> &syn::punctuated::Punctuated<Field, Token
     unimplemented!()
 }
 
+// how a field is stored in the builder and what its setter takes
+enum FieldKind<'a> {
+    // must be set before `build`, stored as `Option<ty>`
+    Required,
+    // stored as is, the setter takes the inner type when it is known
+    Optional(Option<&'a syn::Type>),
+    // `each` field of type `Vec<item>` or `Option<Vec<item>>`
+    Repeated { item: &'a syn::Type, optional: bool },
+}
+
+fn get_field_kind<'a>(ty: &'a syn::Type, attrs: &FieldAttrs) -> syn::Result<FieldKind<'a>> {
+    let inner_option = get_inner("Option", ty);
+    if let Some(ref lit) = attrs.each {
+        if attrs.optional || attrs.required {
+            return Err(syn::Error::new_spanned(
+                lit,
+                "`each` cannot be combined with `optional` or `required`",
+            ));
+        }
+        let (vec, optional) = match inner_option {
+            Some(inner) => (inner, true),
+            None => (ty, false),
+        };
+        return match get_inner("Vec", vec) {
+            Some(item) => Ok(FieldKind::Repeated { item, optional }),
+            None => Err(syn::Error::new_spanned(
+                ty,
+                "`each` requires a field of type `Vec<T>` or `Option<Vec<T>>`",
+            )),
+        };
+    }
+    if attrs.required {
+        return Ok(FieldKind::Required);
+    }
+    if attrs.optional || inner_option.is_some() {
+        return Ok(FieldKind::Optional(inner_option));
+    }
+    Ok(FieldKind::Required)
+}
+
+// `Box`, `Arc` and `Rc` setters accept anything convertible, e.g. `&str` for
+// `Box<str>`. Trait objects are left alone since they need an unsizing coercion.
+fn is_smart_pointer(ty: &syn::Type) -> bool {
+    ["Box", "Arc", "Rc"].iter().any(|wrapper| {
+        !matches!(
+            get_inner(wrapper, ty),
+            None | Some(syn::Type::TraitObject(_) | syn::Type::ImplTrait(_))
+        )
+    })
+}
+
+// `Option`, `option::Option`, `std::option::Option`, `::core::option::Option`...
+fn is_wrapper_path(wrapper: &str, path: &syn::Path) -> bool {
+    let module = match wrapper {
+        "Option" => "option",
+        "Vec" => "vec",
+        "Box" => "boxed",
+        "Arc" => "sync",
+        "Rc" => "rc",
+        _ => return false,
+    };
+    let v: Vec<_> = path.segments.iter().map(|e| e.ident.to_string()).collect();
+    let v: Vec<_> = v.iter().map(String::as_str).collect();
+    match v[..] {
+        [name] => path.leading_colon.is_none() && name == wrapper,
+        [m, name] => m == module && name == wrapper,
+        [krate, m, name] => {
+            matches!(krate, "std" | "core" | "alloc") && m == module && name == wrapper
+        }
+        _ => false,
+    }
+}
+
 fn get_inner<'a>(wrapper: &str, ty: &'a syn::Type) -> Option<&'a syn::Type> {
-    if let syn::Type::Path(syn::TypePath {
-        path: syn::Path { segments, .. },
-        ..
-    }) = ty
-    {
-        let v: Vec<_> = segments.iter().map(|e| e.ident.to_string()).collect();
-        if v.last()? == wrapper {
-            if let syn::PathSegment {
-                arguments:
-                    syn::PathArguments::AngleBracketed(syn::AngleBracketedGenericArguments {
-                        args, ..
-                    }),
-                ..
-            } = segments.last()?
-            {
-                if let syn::GenericArgument::Type(tp) = args.first()? {
-                    return Some(tp);
-                }
+    if let syn::Type::Path(syn::TypePath { qself: None, path }) = ty {
+        if !is_wrapper_path(wrapper, path) {
+            return None;
+        }
+        if let syn::PathArguments::AngleBracketed(syn::AngleBracketedGenericArguments {
+            args,
+            ..
+        }) = &path.segments.last()?.arguments
+        {
+            if let (1, Some(syn::GenericArgument::Type(tp))) = (args.len(), args.first()) {
+                return Some(tp);
             }
         }
     }
     None
 }
 
-fn get_meta(e: &syn::Field) -> Option<syn::Meta> {
-    e.attrs.get(0)?.parse_meta().ok()
+// options collected from `#[builder(...)]` on the struct
+#[derive(Default)]
+struct StructAttrs {
+    take: bool,
+    // `#[builder(serde)]`, only available with the `serde` feature
+    serde: bool,
 }
 
-fn get_lit_from_meta(m: &syn::Meta) -> Result<&syn::LitStr, Box<dyn std::error::Error>> {
-    let err = Err("expected `builder(each = \"...\")`");
-    if let syn::Meta::List(syn::MetaList { path, nested, .. }) = m {
-        if path.segments[0].ident != "builder" {
-            return err?;
-        }
-        if let syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
-            path, lit, ..
-        })) = &nested[0]
-        {
-            if path.segments[0].ident != "each" {
-                return err?;
+fn get_struct_attrs(attrs: &[syn::Attribute]) -> syn::Result<StructAttrs> {
+    let mut struct_attrs = StructAttrs::default();
+    for attr in attrs.iter().filter(|a| a.path.is_ident("builder")) {
+        let meta = attr.parse_meta()?;
+        let err = || syn::Error::new_spanned(&meta, "expected `builder(take)` or `builder(serde)`");
+        let syn::Meta::List(syn::MetaList { nested, .. }) = &meta else {
+            return Err(err());
+        };
+        for nested in nested {
+            match nested {
+                syn::NestedMeta::Meta(syn::Meta::Path(p)) if p.is_ident("take") => {
+                    struct_attrs.take = true;
+                }
+                syn::NestedMeta::Meta(syn::Meta::Path(p)) if p.is_ident("serde") => {
+                    if !cfg!(feature = "serde") {
+                        return Err(syn::Error::new_spanned(
+                            p,
+                            "`builder(serde)` requires the `serde` feature of derive_builder",
+                        ));
+                    }
+                    struct_attrs.serde = true;
+                }
+                _ => return Err(err()),
             }
-            if let syn::Lit::Str(ls) = lit {
-                return Ok(ls);
-            } else {
-                return err?;
+        }
+    }
+    Ok(struct_attrs)
+}
+
+// options collected from `#[builder(...)]` on a field
+#[derive(Default)]
+struct FieldAttrs {
+    each: Option<syn::LitStr>,
+    each_into: bool,
+    custom_setter: bool,
+    optional: bool,
+    required: bool,
+}
+
+fn get_field_attrs(e: &syn::Field) -> syn::Result<FieldAttrs> {
+    let mut attrs = FieldAttrs::default();
+    for attr in e.attrs.iter().filter(|a| a.path.is_ident("builder")) {
+        let meta = attr.parse_meta()?;
+        let err = || syn::Error::new_spanned(&meta, "expected `builder(each = \"...\")`");
+        let syn::Meta::List(syn::MetaList { nested, .. }) = &meta else {
+            return Err(err());
+        };
+        for nested in nested {
+            match nested {
+                syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                    path,
+                    lit: syn::Lit::Str(ls),
+                    ..
+                })) if path.is_ident("each") => attrs.each = Some(ls.clone()),
+                // each(name = "...", into)
+                syn::NestedMeta::Meta(syn::Meta::List(syn::MetaList { path, nested, .. }))
+                    if path.is_ident("each") =>
+                {
+                    let each_err = |n: &dyn quote::ToTokens| {
+                        syn::Error::new_spanned(n, "expected `each(name = \"...\", into)`")
+                    };
+                    for n in nested {
+                        match n {
+                            syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                                path,
+                                lit: syn::Lit::Str(ls),
+                                ..
+                            })) if path.is_ident("name") => attrs.each = Some(ls.clone()),
+                            syn::NestedMeta::Meta(syn::Meta::Path(p)) if p.is_ident("into") => {
+                                attrs.each_into = true;
+                            }
+                            _ => return Err(each_err(n)),
+                        }
+                    }
+                    if attrs.each.is_none() {
+                        return Err(each_err(path));
+                    }
+                }
+                // optional / required override the inferred kind, e.g. for type aliases
+                syn::NestedMeta::Meta(syn::Meta::Path(p)) if p.is_ident("optional") => {
+                    attrs.optional = true;
+                }
+                syn::NestedMeta::Meta(syn::Meta::Path(p)) if p.is_ident("required") => {
+                    attrs.required = true;
+                }
+                // setter(custom)
+                syn::NestedMeta::Meta(syn::Meta::List(syn::MetaList { path, nested, .. }))
+                    if path.is_ident("setter") =>
+                {
+                    for n in nested {
+                        match n {
+                            syn::NestedMeta::Meta(syn::Meta::Path(p)) if p.is_ident("custom") => {
+                                attrs.custom_setter = true;
+                            }
+                            _ => {
+                                return Err(syn::Error::new_spanned(n, "expected `setter(custom)`"))
+                            }
+                        }
+                    }
+                }
+                _ => return Err(err()),
             }
         }
     }
-    err?
+    if attrs.optional && attrs.required {
+        return Err(syn::Error::new_spanned(
+            &e.ident,
+            "field cannot be both `optional` and `required`",
+        ));
+    }
+    Ok(attrs)
 }
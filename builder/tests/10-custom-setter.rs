@@ -0,0 +1,54 @@
+// Some fields need a setter that does more than store the value, for example
+// normalizing a hostname before it is kept. Writing the whole builder by hand
+// just for that one method would be a shame.
+//
+// A field annotated with #[builder(setter(custom))] keeps its storage slot in
+// the builder and takes part in `build` exactly like any other field, but no
+// setter is generated for it. The caller writes the setter themselves in a
+// separate impl block, without running into a duplicate definition.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Connection {
+    #[builder(setter(custom))]
+    host: String,
+    port: u16,
+    #[builder(setter(custom))]
+    user: Option<String>,
+}
+
+impl ConnectionBuilder {
+    fn host(&mut self, host: &str) -> &mut Self {
+        self.host = Some(host.to_lowercase());
+        self
+    }
+
+    fn user(&mut self, user: &str) -> &mut Self {
+        self.user = Some(user.trim().to_owned());
+        self
+    }
+}
+
+fn main() {
+    let conn = Connection::builder()
+        .host("Example.COM")
+        .port(5432)
+        .build()
+        .unwrap();
+
+    assert_eq!(conn.host, "example.com");
+    assert_eq!(conn.port, 5432);
+    assert!(conn.user.is_none());
+
+    let conn = Connection::builder()
+        .host("localhost")
+        .port(22)
+        .user("  admin ")
+        .build()
+        .unwrap();
+
+    assert_eq!(conn.user.as_deref(), Some("admin"));
+
+    assert!(Connection::builder().port(80).build().is_err());
+}
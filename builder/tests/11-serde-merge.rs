@@ -0,0 +1,60 @@
+// With the `serde` feature enabled, #[builder(serde)] makes the builder derive
+// serde::Deserialize so that partial configuration can be loaded straight from
+// a file. Builders without the attribute are unaffected, so their fields don't
+// need to implement Deserialize. Every field is optional in the serialized
+// form; anything missing is simply left unset.
+//
+// Layers are combined with `merge`, which takes every field that has been set
+// in `other` and leaves the rest alone. Loading defaults first and overrides
+// later, then calling `build`, yields the final struct.
+//
+// This test only runs with `cargo test --features serde`.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(serde)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    #[builder(each = "env")]
+    env: Vec<String>,
+    current_dir: Option<String>,
+}
+
+fn main() {
+    let mut builder: CommandBuilder = serde_json::from_str(
+        r#"{
+            "executable": "cargo",
+            "args": ["build"],
+            "current_dir": "/tmp"
+        }"#,
+    )
+    .unwrap();
+
+    let overrides: CommandBuilder = serde_json::from_str(
+        r#"{
+            "args": ["build", "--release"],
+            "env": ["RUST_LOG=debug"]
+        }"#,
+    )
+    .unwrap();
+
+    let command = builder.merge(overrides).build().unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert_eq!(command.env, vec!["RUST_LOG=debug"]);
+    assert_eq!(command.current_dir.as_deref(), Some("/tmp"));
+
+    let empty: CommandBuilder = serde_json::from_str("{}").unwrap();
+    let command = Command::builder()
+        .executable("rustc".to_owned())
+        .arg("--version".to_owned())
+        .merge(empty)
+        .build()
+        .unwrap();
+    assert_eq!(command.executable, "rustc");
+    assert_eq!(command.args, vec!["--version"]);
+    assert!(command.current_dir.is_none());
+}
@@ -0,0 +1,59 @@
+// The default `build` clones every field out of the builder so that it can be
+// called more than once. That rules out fields whose type isn't Clone, such as
+// an open file or a boxed closure.
+//
+// Every builder also gets a `build_take` method which moves the values out
+// instead, leaving the builder empty afterwards. Putting #[builder(take)] on
+// the struct makes `build` behave the same way, so no field needs to be Clone.
+//
+// A required field that is missing must not cause the fields that were already
+// set to be lost.
+
+use derive_builder::Builder;
+
+pub struct Handle {
+    fd: i32,
+}
+
+#[derive(Builder)]
+#[builder(take)]
+pub struct Job {
+    name: String,
+    handle: Handle,
+    callback: Box<dyn Fn(i32) -> i32>,
+    #[builder(each = "input")]
+    inputs: Vec<Handle>,
+    output: Option<Handle>,
+}
+
+fn main() {
+    let mut builder = Job::builder();
+    builder
+        .name("compile".to_owned())
+        .callback(Box::new(|x| x * 2))
+        .input(Handle { fd: 3 })
+        .input(Handle { fd: 4 });
+
+    // `handle` is missing; nothing set so far may be dropped
+    assert!(builder.build().is_err());
+
+    let job = builder.handle(Handle { fd: 1 }).build().unwrap();
+    assert_eq!(job.name, "compile");
+    assert_eq!(job.handle.fd, 1);
+    assert_eq!((job.callback)(21), 42);
+    assert_eq!(job.inputs.iter().map(|h| h.fd).collect::<Vec<_>>(), [3, 4]);
+    assert!(job.output.is_none());
+
+    // the values have been moved out of the builder
+    assert!(builder.build().is_err());
+
+    let job = Job::builder()
+        .name("link".to_owned())
+        .handle(Handle { fd: 5 })
+        .callback(Box::new(|x| x))
+        .output(Handle { fd: 6 })
+        .build_take()
+        .unwrap();
+    assert!(job.inputs.is_empty());
+    assert_eq!(job.output.unwrap().fd, 6);
+}
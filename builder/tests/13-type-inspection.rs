@@ -0,0 +1,77 @@
+// Since the macro only sees tokens, it recognizes Option and Vec by how they
+// are written. Fully qualified paths like std::option::Option<T> are
+// understood, as is an `each` field of type Option<Vec<T>>, which stays None
+// until the first element is pushed.
+//
+// Type aliases can't be seen through. For those the caller says explicitly
+// what they mean with #[builder(optional)] or #[builder(required)]. A field
+// marked optional has to be an alias of Option. A field of type Option<T>
+// marked required must be set, possibly to None, before build.
+//
+// Setters for Box, Arc and Rc fields accept anything that converts into the
+// pointer, so a Box<str> field can be set from a &str.
+
+use derive_builder::Builder;
+use std::rc::Rc;
+use std::sync::Arc;
+
+type MaybeDir = Option<String>;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: Box<str>,
+    #[builder(each = "arg")]
+    args: std::vec::Vec<String>,
+    #[builder(each = "env")]
+    env: Option<Vec<String>>,
+    current_dir: std::option::Option<String>,
+    #[builder(optional)]
+    home_dir: MaybeDir,
+    #[builder(required)]
+    umask: Option<u32>,
+    name: Arc<str>,
+    shared: Rc<Vec<u8>>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo")
+        .arg("build".to_owned())
+        .current_dir("..".to_owned())
+        .umask(None)
+        .name(String::from("job"))
+        .shared(vec![1, 2, 3])
+        .build()
+        .unwrap();
+
+    assert_eq!(&*command.executable, "cargo");
+    assert_eq!(command.args, vec!["build"]);
+    assert!(command.env.is_none());
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+    assert!(command.home_dir.is_none());
+    assert!(command.umask.is_none());
+    assert_eq!(&*command.name, "job");
+    assert_eq!(*command.shared, [1, 2, 3]);
+
+    let command = Command::builder()
+        .executable("rustc")
+        .env("RUST_LOG=debug".to_owned())
+        .home_dir(Some("/root".to_owned()))
+        .umask(Some(0o22))
+        .name("job")
+        .shared(Rc::new(vec![]))
+        .build()
+        .unwrap();
+
+    assert_eq!(command.env, Some(vec!["RUST_LOG=debug".to_owned()]));
+    assert_eq!(command.home_dir.as_deref(), Some("/root"));
+    assert_eq!(command.umask, Some(0o22));
+
+    // umask is required even though it is an Option
+    assert!(Command::builder()
+        .executable("cargo")
+        .name("job")
+        .shared(vec![])
+        .build()
+        .is_err());
+}
@@ -0,0 +1,16 @@
+// An `each` field has to be a Vec<T> or an Option<Vec<T>> so that the macro
+// knows the element type taken by the one-at-a-time setter. Anything else,
+// including a type alias for a Vec, is reported at the field's type.
+
+use derive_builder::Builder;
+
+type Args = Vec<String>;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Args,
+}
+
+fn main() {}
@@ -0,0 +1,40 @@
+// The one-at-a-time setter can also be configured with the nested form
+// #[builder(each(name = "...", into))]. With `into` the setter accepts anything
+// that converts into the element type, so string literals can be pushed into a
+// Vec<String> without an explicit `.to_owned()` at every call.
+//
+// Every `each` field additionally gets an `extend_<field>` method taking any
+// iterator whose items convert into the element type.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each(name = "arg", into))]
+    args: Vec<String>,
+    #[builder(each(name = "env"))]
+    env: Vec<String>,
+    #[builder(each = "path")]
+    paths: Option<Vec<std::path::PathBuf>>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .arg("build")
+        .arg(String::from("--release"))
+        .extend_args(["--features", "serde"])
+        .env("RUST_LOG=debug".to_owned())
+        .extend_env(vec!["A=1".to_owned()])
+        .extend_paths(["/usr/bin", "/bin"])
+        .build()
+        .unwrap();
+
+    assert_eq!(command.args, ["build", "--release", "--features", "serde"]);
+    assert_eq!(command.env, ["RUST_LOG=debug", "A=1"]);
+    assert_eq!(
+        command.paths.unwrap(),
+        [std::path::Path::new("/usr/bin"), std::path::Path::new("/bin")],
+    );
+}
@@ -0,0 +1,12 @@
+// The nested form of `each` still needs a name for the one-at-a-time setter.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each(into))]
+    args: Vec<String>,
+}
+
+fn main() {}
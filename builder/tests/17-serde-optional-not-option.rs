@@ -0,0 +1,17 @@
+// `merge` only takes the fields that are set in `other`, so with
+// #[builder(serde)] a field marked #[builder(optional)] has to be an alias of
+// Option. The error points at the type that isn't one.
+//
+// This test only runs with `cargo test --features serde`.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(serde)]
+pub struct Command {
+    executable: String,
+    #[builder(optional)]
+    umask: u32,
+}
+
+fn main() {}
@@ -10,4 +10,14 @@ fn tests() {
     t.pass("tests/07-repeated-field.rs");
     t.compile_fail("tests/08-unrecognized-attribute.rs");
     t.pass("tests/09-redefined-prelude-types.rs");
+    t.pass("tests/10-custom-setter.rs");
+    #[cfg(feature = "serde")]
+    t.pass("tests/11-serde-merge.rs");
+    t.pass("tests/12-non-clone-fields.rs");
+    t.pass("tests/13-type-inspection.rs");
+    t.compile_fail("tests/14-each-not-vec.rs");
+    t.pass("tests/15-each-into.rs");
+    t.compile_fail("tests/16-each-missing-name.rs");
+    #[cfg(feature = "serde")]
+    t.compile_fail("tests/17-serde-optional-not-option.rs");
 }
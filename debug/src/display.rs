@@ -0,0 +1,226 @@
+use quote::quote;
+
+pub(crate) fn expand(input: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let ident = &input.ident;
+    let gen_idents = crate::get_gen_idents(&input.generics);
+
+    // field types paired with the formatting trait the format strings use them with
+    let mut display_types = vec![];
+    let mut arms = vec![];
+
+    match &input.data {
+        syn::Data::Struct(syn::DataStruct { fields, .. }) => {
+            let lit = get_display_lit(&input.attrs, ident)?;
+            arms.push(display_arm(quote!(Self), fields, &lit, &mut display_types)?);
+        }
+        syn::Data::Enum(syn::DataEnum { variants, .. }) => {
+            for v in variants {
+                let variant = &v.ident;
+                let lit = get_display_lit(&v.attrs, variant)?;
+                arms.push(display_arm(
+                    quote!(Self::#variant),
+                    &v.fields,
+                    &lit,
+                    &mut display_types,
+                )?);
+            }
+        }
+        syn::Data::Union(syn::DataUnion { union_token, .. }) => {
+            return Err(syn::Error::new_spanned(
+                union_token,
+                "CustomDisplay does not support unions",
+            ))
+        }
+    }
+
+    let mut generics = input.generics.clone();
+    generics
+        .make_where_clause()
+        .predicates
+        .extend(crate::impl_predicates(
+            &gen_idents,
+            None,
+            display_types,
+            vec![],
+        ));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let body = if arms.is_empty() {
+        quote! { match *self {} }
+    } else {
+        quote! {
+            match self {
+                #(#arms)*
+            }
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::core::fmt::Display for #ident #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                #body
+            }
+        }
+    })
+}
+
+// `#[display("...")]` on the struct or variant
+fn get_display_lit(attrs: &[syn::Attribute], ident: &syn::Ident) -> syn::Result<syn::LitStr> {
+    match attrs.iter().find(|a| a.path.is_ident("display")) {
+        Some(attr) => attr.parse_args(),
+        None => Err(syn::Error::new_spanned(
+            ident,
+            "missing `#[display(\"...\")]` attribute",
+        )),
+    }
+}
+
+// one match arm binding the fields and writing the format string
+fn display_arm<'a>(
+    path: proc_macro2::TokenStream,
+    fields: &'a syn::Fields,
+    lit: &syn::LitStr,
+    display_types: &mut Vec<(&'a syn::Type, syn::Path)>,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let (pattern, bindings) = crate::variant_pattern(fields);
+    let fields: Vec<_> = fields.iter().collect();
+
+    let (format, args, _) = parse_format(lit, false)?;
+    let mut names = vec![];
+    let mut values = vec![];
+    for FormatArg { name, arg, bound } in args {
+        let index = fields
+            .iter()
+            .enumerate()
+            .position(|(i, f)| match &f.ident {
+                Some(ident) => ident == &name,
+                None => i.to_string() == name,
+            })
+            .ok_or_else(|| syn::Error::new_spanned(lit, format!("unknown field `{}`", name)))?;
+        display_types.push((&fields[index].ty, bound));
+        let arg = quote::format_ident!("{}", arg);
+        if !names.contains(&arg) {
+            names.push(arg);
+            values.push(&bindings[index]);
+        }
+    }
+    let format = syn::LitStr::new(&format, lit.span());
+
+    Ok(quote! {
+        #path #pattern => ::core::write!(f, #format, #(#names = #values),*),
+    })
+}
+
+// one `{name:spec}` placeholder of a format string
+pub(crate) struct FormatArg {
+    // field name or tuple index
+    pub(crate) name: String,
+    // name the argument is passed as
+    pub(crate) arg: String,
+    // formatting trait required by the spec
+    pub(crate) bound: syn::Path,
+}
+
+// Rewrites the format string so every argument is passed by name, positional
+// `{0}` becoming `{__0}` and `{self.field}` becoming `{field}`. With
+// `positional`, `{}` and `{0}` are kept as positional arguments and only
+// `{field}` or `{self.0}` refer to fields; the formatting traits the kept
+// positional arguments require are returned in order, one per placeholder.
+pub(crate) fn parse_format(
+    lit: &syn::LitStr,
+    positional: bool,
+) -> syn::Result<(String, Vec<FormatArg>, Vec<syn::Path>)> {
+    let value = lit.value();
+    let mut format = String::new();
+    let mut args = vec![];
+    let mut positional_bounds = vec![];
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                format.push_str("{{");
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                format.push_str("}}");
+            }
+            '{' => {
+                let mut inner = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => inner.push(c),
+                        None => {
+                            return Err(syn::Error::new_spanned(
+                                lit,
+                                "unterminated `{` in format string",
+                            ))
+                        }
+                    }
+                }
+                let (name, spec) = match inner.split_once(':') {
+                    Some((name, spec)) => (name.trim(), Some(spec)),
+                    None => (inner.trim(), None),
+                };
+                let (name, is_self) = match name.strip_prefix("self.") {
+                    Some(name) => (name.trim(), true),
+                    None => (name, false),
+                };
+                if positional && !is_self && name.chars().all(|c| c.is_ascii_digit()) {
+                    // the index is dropped, each placeholder takes its own argument
+                    format.push('{');
+                    if let Some(spec) = spec {
+                        format.push(':');
+                        format.push_str(spec);
+                    }
+                    format.push('}');
+                    positional_bounds.push(spec_bound(spec));
+                    continue;
+                }
+                if name.is_empty() {
+                    return Err(syn::Error::new_spanned(
+                        lit,
+                        "format arguments must name a field, e.g. `{field}` or `{0}`",
+                    ));
+                }
+                let arg = if name.chars().all(|c| c.is_ascii_digit()) {
+                    format!("__{}", name)
+                } else {
+                    name.to_owned()
+                };
+                let bound = spec_bound(spec);
+                format.push('{');
+                format.push_str(&arg);
+                if let Some(spec) = spec {
+                    format.push(':');
+                    format.push_str(spec);
+                }
+                format.push('}');
+                args.push(FormatArg {
+                    name: name.to_owned(),
+                    arg,
+                    bound,
+                });
+            }
+            c => format.push(c),
+        }
+    }
+    Ok((format, args, positional_bounds))
+}
+
+// formatting trait required by the spec of a placeholder, `x` requiring
+// LowerHex and so on
+fn spec_bound(spec: Option<&str>) -> syn::Path {
+    match spec.and_then(|spec| spec.chars().last()) {
+        Some('?') => syn::parse_quote!(::core::fmt::Debug),
+        Some('x') => syn::parse_quote!(::core::fmt::LowerHex),
+        Some('X') => syn::parse_quote!(::core::fmt::UpperHex),
+        Some('o') => syn::parse_quote!(::core::fmt::Octal),
+        Some('b') => syn::parse_quote!(::core::fmt::Binary),
+        Some('e') => syn::parse_quote!(::core::fmt::LowerExp),
+        Some('E') => syn::parse_quote!(::core::fmt::UpperExp),
+        Some('p') => syn::parse_quote!(::core::fmt::Pointer),
+        _ => syn::parse_quote!(::core::fmt::Display),
+    }
+}
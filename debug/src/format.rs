@@ -0,0 +1,212 @@
+use quote::quote;
+
+pub(crate) fn expand(input: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let ident = &input.ident;
+    let struct_attrs = crate::get_struct_attrs(&input.attrs)?;
+    if struct_attrs.transparent
+        || struct_attrs.skip_none
+        || struct_attrs.raw_bytes
+        || struct_attrs.discriminant
+    {
+        return Err(syn::Error::new_spanned(
+            ident,
+            "`transparent`, `skip_none`, `raw_bytes` and `discriminant` are not supported by CustomFormat",
+        ));
+    }
+    let gen_idents = crate::get_gen_idents(&input.generics);
+
+    // types of the fields written with their own CompactFormat impl
+    let mut format_types = vec![];
+    // explicit `#[debug(bound = "...")]` on fields
+    let mut field_bounds = vec![];
+    // every format string of the type with its id
+    let mut formats = vec![];
+
+    let body = match &input.data {
+        syn::Data::Struct(syn::DataStruct { fields, .. }) => {
+            let name = match &struct_attrs.rename {
+                Some(lit) => lit.value(),
+                None => ident.to_string(),
+            };
+            let members = fields.iter().enumerate().map(|(i, f)| match &f.ident {
+                Some(ident) => quote! { &self.#ident },
+                None => {
+                    let index = syn::Index::from(i);
+                    quote! { &self.#index }
+                }
+            });
+            let (format, writes) =
+                format_fields(&name, fields, members, &mut format_types, &mut field_bounds)?;
+            let id = intern(&format);
+            formats.push(quote! { (#id, #format) });
+            quote! {
+                CompactFormat::write(&#id, w);
+                #(#writes)*
+            }
+        }
+        syn::Data::Enum(syn::DataEnum {
+            enum_token,
+            variants,
+            ..
+        }) => {
+            if struct_attrs.rename.is_some() {
+                return Err(syn::Error::new_spanned(
+                    enum_token,
+                    "`rename` is not supported on enums",
+                ));
+            }
+            let mut arms = vec![];
+            for v in variants {
+                let variant = &v.ident;
+                let (pattern, bindings) = crate::variant_pattern(&v.fields);
+                let (format, writes) = format_fields(
+                    &variant.to_string(),
+                    &v.fields,
+                    bindings.iter().map(|b| quote! { #b }),
+                    &mut format_types,
+                    &mut field_bounds,
+                )?;
+                let id = intern(&format);
+                formats.push(quote! { (#id, #format) });
+                arms.push(quote! {
+                    Self::#variant #pattern => {
+                        CompactFormat::write(&#id, w);
+                        #(#writes)*
+                    }
+                });
+            }
+            if variants.is_empty() {
+                quote! { match *self {} }
+            } else {
+                quote! {
+                    match self {
+                        #(#arms)*
+                    }
+                }
+            }
+        }
+        syn::Data::Union(syn::DataUnion { union_token, .. }) => {
+            return Err(syn::Error::new_spanned(
+                union_token,
+                "CustomFormat does not support unions",
+            ))
+        }
+    };
+
+    let mut generics = input.generics.clone();
+    generics
+        .make_where_clause()
+        .predicates
+        .extend(crate::impl_predicates(
+            &gen_idents,
+            struct_attrs.bound,
+            format_types,
+            field_bounds,
+        ));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // `CompactFormat` and `Write` are resolved where the derive is used
+    Ok(quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Format strings written by `CompactFormat::write`, with their ids.
+            pub const COMPACT_FORMATS: &'static [(u32, &'static str)] = &[#(#formats),*];
+        }
+
+        impl #impl_generics CompactFormat for #ident #ty_generics #where_clause {
+            fn write(&self, w: &mut impl Write) {
+                #body
+            }
+        }
+    })
+}
+
+// The format string for one struct or variant, in the shape CustomDebug prints
+// it, and the statements writing each field that isn't skipped or redacted.
+fn format_fields<'a>(
+    name: &str,
+    fields: &'a syn::Fields,
+    values: impl Iterator<Item = proc_macro2::TokenStream>,
+    format_types: &mut Vec<(&'a syn::Type, syn::Path)>,
+    field_bounds: &mut Vec<syn::WherePredicate>,
+) -> syn::Result<(syn::LitStr, Vec<proc_macro2::TokenStream>)> {
+    let mut parts = vec![];
+    let mut writes = vec![];
+    let mut non_exhaustive = false;
+    for (f, value) in fields.iter().zip(values) {
+        let attrs = crate::get_field_attrs(&f.attrs)?;
+        if attrs.with.is_some() || attrs.ptr || attrs.max_len.is_some() || attrs.skip_if.is_some() {
+            return Err(syn::Error::new_spanned(
+                f,
+                "`with`, `ptr`, `max_len` and `skip_if` are not supported by CustomFormat",
+            ));
+        }
+        if attrs.skip {
+            non_exhaustive = true;
+            continue;
+        }
+        let placeholder = if let Some(redact) = &attrs.redact {
+            escape(&redact.value())
+        } else {
+            // the field is written once for every positional placeholder, the
+            // table entry keeping one `{}` per write
+            let (placeholder, count) = match &attrs.format {
+                Some(lit) => {
+                    let (format, args, positional) = crate::display::parse_format(lit, true)?;
+                    if !args.is_empty() {
+                        return Err(syn::Error::new_spanned(
+                            lit,
+                            "CustomFormat only supports positional placeholders like `{}` or `{:x}`, not field references",
+                        ));
+                    }
+                    (format, positional.len())
+                }
+                None => ("{}".to_owned(), 1),
+            };
+            if count > 0 {
+                match attrs.bound {
+                    Some(bound) => field_bounds.extend(bound),
+                    None => format_types.push((&f.ty, syn::parse_quote!(CompactFormat))),
+                }
+            }
+            writes.extend((0..count).map(|_| quote! { CompactFormat::write(#value, w); }));
+            placeholder
+        };
+        parts.push(match (&f.ident, &attrs.rename) {
+            (Some(_), Some(lit)) => format!("{}: {}", escape(&lit.value()), placeholder),
+            (Some(ident), None) => format!("{}: {}", ident, placeholder),
+            (None, Some(lit)) => {
+                return Err(syn::Error::new_spanned(
+                    lit,
+                    "`rename` requires a named field",
+                ))
+            }
+            (None, None) => placeholder,
+        });
+    }
+    if non_exhaustive {
+        parts.push("..".to_owned());
+    }
+    let format = match fields {
+        syn::Fields::Named(_) if parts.is_empty() => escape(name),
+        syn::Fields::Named(_) => format!("{} {{{{ {} }}}}", escape(name), parts.join(", ")),
+        syn::Fields::Unnamed(_) if parts.is_empty() => escape(name),
+        syn::Fields::Unnamed(_) => format!("{}({})", escape(name), parts.join(", ")),
+        syn::Fields::Unit => escape(name),
+    };
+    Ok((
+        syn::LitStr::new(&format, proc_macro2::Span::call_site()),
+        writes,
+    ))
+}
+
+fn escape(s: &str) -> String {
+    s.replace('{', "{{").replace('}', "}}")
+}
+
+// Id of a format string, the 32-bit FNV-1a hash of its text, so the same
+// string gets the same id in every build.
+fn intern(format: &syn::LitStr) -> u32 {
+    format.value().bytes().fold(0x811c_9dc5, |hash: u32, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    })
+}
@@ -1,167 +1,1036 @@
 use proc_macro::TokenStream;
-use quote::quote;
+use quote::{quote, ToTokens};
 use syn::{parse_macro_input, DeriveInput};
 
+mod display;
+mod format;
+
 #[proc_macro_derive(CustomDebug, attributes(debug))]
 pub fn derive(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
-    // dbg!(&input);
+    expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(CustomDisplay, attributes(display))]
+pub fn derive_display(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    display::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(CustomFormat, attributes(debug))]
+pub fn derive_format(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    format::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
     let ident = &input.ident;
-    let ident_lit = syn::LitStr::new(&ident.to_string(), proc_macro2::Span::call_site());
-    let mut field_methods = vec![];
 
-    let fields = get_fields(&input);
+    let fields = get_fields(input)?;
+    let struct_attrs = get_struct_attrs(&input.attrs)?;
 
-    // ident of type that included in PhantomData
-    let mut phantom_types = vec![];
-    let mut ass_types = vec![];
-    let mut gen_idents = vec![];
-    input.generics.params.iter().for_each(|e| {
-        if let syn::GenericParam::Type(syn::TypeParam { ident, .. }) = e {
-            gen_idents.push(ident.clone());
-        }
-    });
+    let gen_idents = get_gen_idents(&input.generics);
+
+    // types of the fields formatted with their own Debug impl
+    let mut debug_types = vec![];
+    // explicit `#[debug(bound = "...")]` on fields, replacing the inferred ones
+    let mut field_bounds = vec![];
 
     for f in fields.iter() {
-        if let Some(syn::Type::Path(syn::TypePath {
-            path: syn::Path { segments, .. },
-            ..
-        })) = get_inner(Some("PhantomData"), &f.ty)
-        {
-            phantom_types.push(&segments[0].ident);
+        let attrs = get_field_attrs(&f.attrs)?;
+        if let Some(bound) = attrs.bound {
+            field_bounds.extend(bound);
+            continue;
         }
-        if let Some(tt) = get_assosiate_type(&f.ty, &gen_idents) {
-            ass_types.push(tt);
-        };
-        let meta = get_meta(&f.attrs);
-        // dbg!(&meta);
-        let lit = syn::LitStr::new(
-            &f.ident.as_ref().unwrap().to_string(),
-            proc_macro2::Span::call_site(),
-        );
-        let field_ident = &f.ident;
-        field_methods.push(if let Some(m) = meta {
-            let lit_meta = get_lit_from_meta(&m);
-            match lit_meta {
-                Ok(lit_meta) => {
-                    quote! {
-                        field(#lit, &format_args!(#lit_meta, &self.#field_ident))
+        // these fields are never formatted with their own Debug impl
+        if attrs.skip || attrs.redact.is_some() || attrs.with.is_some() || attrs.ptr {
+            continue;
+        }
+        // a format string decides the traits through the specs of its `{}`
+        if let Some(lit) = &attrs.format {
+            let (_, _, positional) = display::parse_format(lit, true)?;
+            debug_types.extend(positional.into_iter().map(|bound| (&f.ty, bound)));
+            continue;
+        }
+        debug_types.push((&f.ty, syn::parse_quote!(::core::fmt::Debug)));
+    }
+
+    let name = struct_attrs
+        .rename
+        .clone()
+        .unwrap_or_else(|| syn::LitStr::new(&ident.to_string(), ident.span()));
+
+    if struct_attrs.discriminant && !matches!(input.data, syn::Data::Enum(_)) {
+        return Err(syn::Error::new_spanned(
+            ident,
+            "`discriminant` is only supported on enums",
+        ));
+    }
+
+    let body = match &input.data {
+        syn::Data::Enum(syn::DataEnum { variants, .. }) if struct_attrs.discriminant => {
+            discriminant_arms(input, variants)?
+        }
+        syn::Data::Struct(_) if struct_attrs.raw_bytes => raw_bytes(input, &name)?,
+        syn::Data::Struct(syn::DataStruct { fields, .. }) => {
+            let members = fields.iter().enumerate().map(|(i, f)| match &f.ident {
+                Some(ident) => quote! { &self.#ident },
+                None => {
+                    let index = syn::Index::from(i);
+                    quote! { &self.#index }
+                }
+            });
+            if struct_attrs.transparent {
+                // newtypes print exactly like their only field
+                let members: Vec<_> = members.collect();
+                match (fields.iter().next(), &members[..]) {
+                    (Some(field), [member]) => {
+                        // options changing how the value prints still apply,
+                        // those about its place in the struct have nothing to act on
+                        let attrs = get_field_attrs(&field.attrs)?;
+                        if attrs.skip || attrs.skip_if.is_some() || attrs.rename.is_some() {
+                            return Err(syn::Error::new_spanned(
+                                field,
+                                "`skip`, `skip_if` and `rename` are not supported on the field of a `transparent` struct",
+                            ));
+                        }
+                        let value = field_value(
+                            field,
+                            &attrs,
+                            member.clone(),
+                            fields,
+                            &members,
+                            &mut debug_types,
+                        )?;
+                        quote! { ::core::fmt::Debug::fmt(#value, f) }
+                    }
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            ident,
+                            "`transparent` requires a struct with exactly one field",
+                        ))
                     }
                 }
-                Err(_) => todo!("Handle case wrong attr name!"),
+            } else {
+                debug_fields(
+                    &name,
+                    fields,
+                    members,
+                    struct_attrs.skip_none,
+                    &mut debug_types,
+                )?
             }
-        } else {
-            quote! {
-                field(#lit, &self.#field_ident)
+        }
+        syn::Data::Enum(syn::DataEnum {
+            enum_token,
+            variants,
+            ..
+        }) => {
+            if struct_attrs.transparent || struct_attrs.rename.is_some() || struct_attrs.raw_bytes {
+                return Err(syn::Error::new_spanned(
+                    enum_token,
+                    "`transparent`, `rename` and `raw_bytes` are not supported on enums",
+                ));
+            }
+            let arms = variants.iter().map(|v| {
+                let variant = &v.ident;
+                let name = syn::LitStr::new(&variant.to_string(), variant.span());
+                let (pattern, bindings) = variant_pattern(&v.fields);
+                let body = debug_fields(
+                    &name,
+                    &v.fields,
+                    bindings.iter().map(|b| quote! { #b }),
+                    struct_attrs.skip_none,
+                    &mut debug_types,
+                )?;
+                Ok(quote! {
+                    Self::#variant #pattern => #body,
+                })
+            });
+            let arms = arms.collect::<syn::Result<Vec<_>>>()?;
+            if variants.is_empty() {
+                quote! { match *self {} }
+            } else {
+                quote! {
+                    match self {
+                        #(#arms)*
+                    }
+                }
+            }
+        }
+        // which field of a union is active is unknown, so none of them is printed
+        syn::Data::Union(_) if !struct_attrs.raw_bytes => quote! {
+            f.debug_struct(#name).finish_non_exhaustive()
+        },
+        syn::Data::Union(_) => raw_bytes(input, &name)?,
+    };
+
+    // formatting through `{:?}` resets the `{:#?}` flag, so everything nested stays on one line
+    let body = if struct_attrs.compact {
+        quote! {
+            struct DebugFn<F>(F)
+            where
+                F: ::core::ops::Fn(&mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result;
+
+            impl<F> ::core::fmt::Debug for DebugFn<F>
+            where
+                F: ::core::ops::Fn(&mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result,
+            {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    (self.0)(f)
+                }
             }
-        })
-    }
+
+            ::core::write!(f, "{:?}", DebugFn(|f: &mut ::core::fmt::Formatter<'_>| #body))
+        }
+    } else {
+        body
+    };
 
     let mut generics = input.generics.clone();
-    let mut where_statement: Vec<_> = ass_types
-        .iter()
-        .map(|e| {
-            quote! {
-                #e : ::std::fmt::Debug,
+    let mut predicates =
+        impl_predicates(&gen_idents, struct_attrs.bound, debug_types, field_bounds);
+    if struct_attrs.raw_bytes {
+        // only plain data may be read byte by byte
+        let (_, ty_generics, _) = input.generics.split_for_impl();
+        predicates.push(syn::parse_quote!(#ident #ty_generics: ::core::marker::Copy));
+    }
+    generics.make_where_clause().predicates.extend(predicates);
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::core::fmt::Debug for #ident #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                #body
+            }
+        }
+    })
+}
+
+// Prints the bytes of the value in hex, `Name(01 00 ff 7f)`. Padding bytes are
+// uninitialized and must never be read, so every field has to be plain data
+// and the fields have to cover the whole value: for a struct their sizes add
+// up to the size of the struct, and every field of a union is as large as the
+// union itself. The sizes are checked when the impl is compiled.
+fn raw_bytes(input: &DeriveInput, name: &syn::LitStr) -> syn::Result<proc_macro2::TokenStream> {
+    if !input.attrs.iter().any(|a| a.path.is_ident("repr")) {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "`raw_bytes` requires a `#[repr(...)]` layout",
+        ));
+    }
+    let fields = match &input.data {
+        syn::Data::Struct(syn::DataStruct { fields, .. }) => fields.iter().collect(),
+        syn::Data::Union(syn::DataUnion { fields, .. }) => fields.named.iter().collect(),
+        syn::Data::Enum(_) => vec![],
+    };
+    for f in &fields {
+        if !is_plain_data(&f.ty) {
+            return Err(syn::Error::new_spanned(
+                &f.ty,
+                "`raw_bytes` requires fields of integer, float or bool types, or arrays of them",
+            ));
+        }
+    }
+    let sizes = fields.iter().map(|f| {
+        let ty = &f.ty;
+        quote! { ::core::mem::size_of::<#ty>() }
+    });
+    let no_padding = match &input.data {
+        syn::Data::Union(_) => quote! {
+            true #(&& #sizes == ::core::mem::size_of::<Self>())*
+        },
+        _ => quote! {
+            0 #(+ #sizes)* == ::core::mem::size_of::<Self>()
+        },
+    };
+    Ok(quote! {
+        const {
+            ::core::assert!(#no_padding, "`raw_bytes` requires a layout without padding");
+        }
+
+        struct RawBytes<'a>(&'a [u8]);
+
+        impl<'a> ::core::fmt::Debug for RawBytes<'a> {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                for (i, byte) in self.0.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(" ")?;
+                    }
+                    ::core::write!(f, "{:02x}", byte)?;
+                }
+                ::core::result::Result::Ok(())
             }
+        }
+
+        // SAFETY: the fields are plain data covering all `size_of::<Self>()`
+        // bytes, as asserted above, so none of the bytes is padding
+        let bytes = unsafe {
+            ::core::slice::from_raw_parts(
+                self as *const Self as *const u8,
+                ::core::mem::size_of::<Self>(),
+            )
+        };
+        f.debug_tuple(#name).field(&RawBytes(bytes)).finish()
+    })
+}
+
+// `match` printing `Name::Variant = N` for an enum made of unit variants, where
+// `N` counts up from the last explicit discriminant like the compiler does
+fn discriminant_arms(
+    input: &DeriveInput,
+    variants: &syn::punctuated::Punctuated<syn::Variant, syn::Token![,]>,
+) -> syn::Result<proc_macro2::TokenStream> {
+    // discriminants have the type given by `#[repr(...)]`, `isize` by default
+    let repr = input
+        .attrs
+        .iter()
+        .filter(|a| a.path.is_ident("repr"))
+        .filter_map(|a| a.parse_args::<syn::Ident>().ok())
+        .find(|ident| {
+            [
+                "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128",
+                "isize",
+            ]
+            .iter()
+            .any(|int| ident == int)
         })
+        .unwrap_or_else(|| quote::format_ident!("isize"));
+    let mut last: Option<&syn::Expr> = None;
+    let mut offset = 0usize;
+    let mut arms = vec![];
+    for v in variants {
+        if !matches!(v.fields, syn::Fields::Unit) {
+            return Err(syn::Error::new_spanned(
+                v,
+                "`discriminant` requires an enum with only unit variants",
+            ));
+        }
+        match &v.discriminant {
+            Some((_, expr)) => {
+                last = Some(expr);
+                offset = 0;
+            }
+            None if arms.is_empty() => {}
+            None => offset += 1,
+        }
+        let offset_lit = proc_macro2::Literal::usize_unsuffixed(offset);
+        let value = match last {
+            Some(expr) => quote! { (#expr) + #offset_lit },
+            None => quote! { #offset_lit },
+        };
+        let variant = &v.ident;
+        let label = syn::LitStr::new(
+            &format!("{}::{} = {{}}", input.ident, variant),
+            variant.span(),
+        );
+        arms.push(quote! {
+            Self::#variant => {
+                const DISCRIMINANT: #repr = #value;
+                ::core::write!(f, #label, DISCRIMINANT)
+            }
+        });
+    }
+    if arms.is_empty() {
+        return Ok(quote! { match *self {} });
+    }
+    Ok(quote! {
+        match self {
+            #(#arms)*
+        }
+    })
+}
+
+// every field of the struct, or of all variants of the enum
+fn get_fields(e: &syn::DeriveInput) -> syn::Result<Vec<&syn::Field>> {
+    match &e.data {
+        syn::Data::Struct(syn::DataStruct { fields, .. }) => Ok(fields.iter().collect()),
+        syn::Data::Enum(syn::DataEnum { variants, .. }) => {
+            Ok(variants.iter().flat_map(|v| v.fields.iter()).collect())
+        }
+        syn::Data::Union(_) => Ok(vec![]),
+    }
+}
+
+// Pattern binding every field of a struct or variant to `__field0`,
+// `__field1` and so on, and those bindings in field order.
+fn variant_pattern(fields: &syn::Fields) -> (proc_macro2::TokenStream, Vec<syn::Ident>) {
+    let bindings: Vec<_> = (0..fields.len())
+        .map(|i| quote::format_ident!("__field{}", i))
         .collect();
-    // dbg!(&ass_types);
-    if let Some(meta) = get_meta(&input.attrs) {
-        let lit = get_lit_from_meta(&meta).unwrap();
-        let val = lit.value();
-        let w: syn::WherePredicate =  syn::parse_str(&val).unwrap();
-        where_statement.clear();
-        where_statement.push(quote! {#w});
-    } else {
-        generics = add_trait_bounds(generics, &phantom_types, &ass_types);
+    let pattern = match fields {
+        syn::Fields::Named(named) => {
+            let names = named.named.iter().map(|f| &f.ident);
+            quote! { { #(#names: #bindings),* } }
+        }
+        syn::Fields::Unnamed(_) => quote! { ( #(#bindings),* ) },
+        syn::Fields::Unit => quote! {},
+    };
+    (pattern, bindings)
+}
+
+// `debug_struct`/`debug_tuple` expression for one struct or variant, where each
+// item of `values` is an expression evaluating to a reference to the field
+fn debug_fields<'a>(
+    name_lit: &syn::LitStr,
+    fields: &'a syn::Fields,
+    values: impl Iterator<Item = proc_macro2::TokenStream>,
+    skip_none: bool,
+    debug_types: &mut Vec<(&'a syn::Type, syn::Path)>,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let values: Vec<_> = values.collect();
+    let mut field_stmts = vec![];
+    let mut non_exhaustive = false;
+    for (f, value) in fields.iter().zip(values.iter().cloned()) {
+        let attrs = get_field_attrs(&f.attrs)?;
+        if attrs.skip {
+            non_exhaustive = true;
+            continue;
+        }
+        // checked at runtime, the field is left out when it holds
+        let skip_if = if let Some(path) = &attrs.skip_if {
+            Some(quote! { #path(#value) })
+        } else if skip_none && get_inner(Some("Option"), &f.ty).is_some() {
+            Some(quote! { ::core::option::Option::is_none(#value) })
+        } else if skip_none && is_collection(&f.ty) {
+            Some(quote! { (#value).is_empty() })
+        } else {
+            None
+        };
+        let value = field_value(f, &attrs, value, fields, &values, debug_types)?;
+        let field_method = match (&f.ident, attrs.rename) {
+            (Some(_), Some(lit)) => quote! { field(#lit, #value) },
+            (Some(field_ident), None) => {
+                let lit =
+                    syn::LitStr::new(&field_ident.to_string(), proc_macro2::Span::call_site());
+                quote! { field(#lit, #value) }
+            }
+            (None, Some(lit)) => {
+                return Err(syn::Error::new_spanned(
+                    lit,
+                    "`rename` requires a named field",
+                ))
+            }
+            (None, None) => quote! { field(#value) },
+        };
+        field_stmts.push(match skip_if {
+            Some(skip_if) => quote! {
+                if !#skip_if {
+                    builder.#field_method;
+                }
+            },
+            None => quote! { builder.#field_method; },
+        });
     }
+    // skipped fields are hinted at with `..`
+    let finish = if non_exhaustive {
+        quote! { finish_non_exhaustive }
+    } else {
+        quote! { finish }
+    };
+    Ok(match fields {
+        syn::Fields::Named(_) => quote! {
+            {
+                let mut builder = f.debug_struct(#name_lit);
+                #(#field_stmts)*
+                builder.#finish()
+            }
+        },
+        syn::Fields::Unnamed(_) => quote! {
+            {
+                let mut builder = f.debug_tuple(#name_lit);
+                #(#field_stmts)*
+                builder.#finish()
+            }
+        },
+        syn::Fields::Unit => quote! { f.write_str(#name_lit) },
+    })
+}
+
+// Expression evaluating to a reference to what is printed for the field, where
+// `value` is a reference to the field itself and `values` to all fields of the
+// struct or variant.
+fn field_value<'a>(
+    f: &'a syn::Field,
+    attrs: &FieldAttrs,
+    value: proc_macro2::TokenStream,
+    fields: &'a syn::Fields,
+    values: &[proc_macro2::TokenStream],
+    debug_types: &mut Vec<(&'a syn::Type, syn::Path)>,
+) -> syn::Result<proc_macro2::TokenStream> {
+    Ok(if let Some(placeholder) = &attrs.redact {
+        quote! { &::core::format_args!("{}", #placeholder) }
+    } else if attrs.ptr {
+        quote! { &::core::format_args!("{:p}", *#value) }
+    } else if let Some(max_len) = &attrs.max_len {
+        // look through references and smart pointers to reach the collection,
+        // and through one `Mutex` or `RwLock` on the way
+        let mut derefs = quote! { * };
+        let mut lock = None;
+        let mut ty = &f.ty;
+        loop {
+            ty = match ty {
+                syn::Type::Reference(syn::TypeReference { elem, .. }) => elem,
+                ty if lock.is_none() && get_inner(Some("Mutex"), ty).is_some() => {
+                    lock = Some((ty, quote! { try_lock }, derefs));
+                    derefs = quote! {};
+                    get_inner(Some("Mutex"), ty).unwrap()
+                }
+                ty if lock.is_none() && get_inner(Some("RwLock"), ty).is_some() => {
+                    lock = Some((ty, quote! { try_read }, derefs));
+                    derefs = quote! {};
+                    get_inner(Some("RwLock"), ty).unwrap()
+                }
+                ty => match ["Box", "Arc", "Rc"]
+                    .iter()
+                    .find_map(|wrapper| get_inner(Some(wrapper), ty))
+                {
+                    Some(inner) => inner,
+                    None => break,
+                },
+            };
+            derefs.extend(quote! { * });
+        }
+        // a lock is taken without blocking, printing `<locked>` while someone
+        // else holds it
+        let list = match lock {
+            None => quote! { MaxLen(&#derefs(#value), #max_len) },
+            Some((lock_ty, method, lock_derefs)) => debug_with(
+                quote! { &#lock_derefs(#value) },
+                quote! {
+                    |lock: &#lock_ty, f: &mut ::core::fmt::Formatter<'_>| {
+                        let guard = match lock.#method() {
+                            ::core::result::Result::Ok(guard) => guard,
+                            ::core::result::Result::Err(::std::sync::TryLockError::Poisoned(err)) => {
+                                err.into_inner()
+                            }
+                            ::core::result::Result::Err(::std::sync::TryLockError::WouldBlock) => {
+                                return f.write_str("<locked>");
+                            }
+                        };
+                        ::core::fmt::Debug::fmt(&MaxLen(&#derefs(guard), #max_len), f)
+                    }
+                },
+            ),
+        };
+        // local wrapper printing at most `max_len` items followed by the number left out
+        quote! {
+            &{
+                struct MaxLen<'a, T: ?Sized>(&'a T, usize);
 
-    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+                impl<'a, T: ?Sized> ::core::fmt::Debug for MaxLen<'a, T>
+                where
+                    &'a T: ::core::iter::IntoIterator,
+                    <&'a T as ::core::iter::IntoIterator>::Item: ::core::fmt::Debug,
+                {
+                    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        let mut list = f.debug_list();
+                        let mut more = 0usize;
+                        for (i, item) in ::core::iter::IntoIterator::into_iter(self.0).enumerate() {
+                            if i < self.1 {
+                                list.entry(&item);
+                            } else {
+                                more += 1;
+                            }
+                        }
+                        if more > 0 {
+                            list.entry(&::core::format_args!("..; {} more", more));
+                        }
+                        list.finish()
+                    }
+                }
 
-    // dbg!(&where_statement);
+                #list
+            }
+        }
+    } else if let Some(with) = &attrs.with {
+        let with = debug_with(value, quote! { #with });
+        quote! { &#with }
+    } else if let Some(lit) = &attrs.format {
+        let (format, args, positional) = display::parse_format(lit, true)?;
+        // other fields the format string refers to by name
+        let mut names = vec![];
+        let mut refs = vec![];
+        let all_fields: Vec<_> = fields.iter().collect();
+        for display::FormatArg { name, arg, bound } in args {
+            let index = all_fields
+                .iter()
+                .enumerate()
+                .position(|(i, f)| match &f.ident {
+                    Some(ident) => ident == &name,
+                    None => i.to_string() == name,
+                })
+                .ok_or_else(|| syn::Error::new_spanned(lit, format!("unknown field `{}`", name)))?;
+            // printing a skipped or redacted field here would leak what it hides
+            let referenced = get_field_attrs(&all_fields[index].attrs)?;
+            if referenced.skip || referenced.redact.is_some() {
+                return Err(syn::Error::new_spanned(
+                    lit,
+                    format!(
+                        "field `{}` is skipped or redacted and cannot be referenced",
+                        name
+                    ),
+                ));
+            }
+            debug_types.push((&all_fields[index].ty, bound));
+            let arg = quote::format_ident!("{}", arg);
+            if !names.contains(&arg) {
+                names.push(arg);
+                refs.push(&values[index]);
+            }
+        }
+        let format = syn::LitStr::new(&format, lit.span());
+        // the field itself is passed once for every positional placeholder
+        let repeated = positional.iter().map(|_| &value);
+        quote! { &::core::format_args!(#format, #(#repeated,)* #(#names = #refs),*) }
+    } else {
+        value
+    })
+}
 
+// Block evaluating to a local wrapper whose Debug impl calls `formatter` with
+// `value`, so a function or closure can be used as a Debug impl.
+fn debug_with(
+    value: proc_macro2::TokenStream,
+    formatter: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
     quote! {
-        impl #impl_generics ::std::fmt::Debug for #ident #ty_generics
-        where #(#where_statement)*
         {
-            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
-                f.debug_struct(#ident_lit)
-                    #(.#field_methods)*
-                    .finish()
+            struct DebugWith<'a, T: ?Sized, F>(&'a T, F)
+            where
+                F: ::core::ops::Fn(&T, &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result;
+
+            impl<'a, T: ?Sized, F> ::core::fmt::Debug for DebugWith<'a, T, F>
+            where
+                F: ::core::ops::Fn(&T, &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result,
+            {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    (self.1)(self.0, f)
+                }
             }
+
+            DebugWith(#value, #formatter)
         }
     }
-    .into()
 }
 
-fn get_fields(e: &syn::DeriveInput) -> &syn::punctuated::Punctuated<syn::Field, syn::token::Comma> {
-    if let syn::Data::Struct(syn::DataStruct {
-        fields: syn::Fields::Named(syn::FieldsNamed { named, .. }),
-        ..
-    }) = &e.data
-    {
-        named
-    } else {
-        unimplemented!()
+// integers, floats, bools and arrays of them, which have no padding and no
+// invalid bytes
+fn is_plain_data(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Array(syn::TypeArray { elem, .. })
+        | syn::Type::Paren(syn::TypeParen { elem, .. })
+        | syn::Type::Group(syn::TypeGroup { elem, .. }) => is_plain_data(elem),
+        syn::Type::Path(syn::TypePath { qself: None, path }) => [
+            "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize",
+            "f32", "f64", "bool",
+        ]
+        .iter()
+        .any(|prim| path.is_ident(prim)),
+        _ => false,
     }
 }
 
-fn get_meta(attrs: &[syn::Attribute]) -> Option<syn::Meta> {
-    attrs.get(0)?.parse_meta().ok()
+// collections left out by `#[debug(skip_none)]` when empty
+fn is_collection(ty: &syn::Type) -> bool {
+    [
+        "Vec",
+        "VecDeque",
+        "BinaryHeap",
+        "HashSet",
+        "BTreeSet",
+        "HashMap",
+        "BTreeMap",
+    ]
+    .iter()
+    .any(|wrapper| get_inner(Some(wrapper), ty).is_some())
+}
+
+// options collected from `#[debug ...]` attributes on a field, at most one of
+// them may be given
+#[derive(Default)]
+struct FieldAttrs {
+    // `#[debug = "..."]`
+    format: Option<syn::LitStr>,
+    // `#[debug(skip)]`
+    skip: bool,
+    // `#[debug(redact)]` or `#[debug(redact = "...")]`
+    redact: Option<syn::LitStr>,
+    // `#[debug(with = "path::to::fn")]`
+    with: Option<syn::Path>,
+    // `#[debug(ptr)]`
+    ptr: bool,
+    // `#[debug(max_len = N)]`
+    max_len: Option<syn::LitInt>,
+    // `#[debug(bound = "...")]`, may be combined with any of the above
+    bound: Option<syn::punctuated::Punctuated<syn::WherePredicate, syn::Token![,]>>,
+    // `#[debug(rename = "...")]`, may be combined with any of the above
+    rename: Option<syn::LitStr>,
+    // `#[debug(skip_if = "path::to::fn")]`, may be combined with any of the above
+    skip_if: Option<syn::Path>,
 }
 
-fn get_lit_from_meta(m: &syn::Meta) -> Result<&syn::LitStr, Box<dyn std::error::Error>> {
-    let err = Err("Wrong sytax");
-    match m {
-        syn::Meta::NameValue(syn::MetaNameValue { path, lit, .. }) => {
-            if path.segments[0].ident != "debug" {
-                return err?;
+fn get_field_attrs(attrs: &[syn::Attribute]) -> syn::Result<FieldAttrs> {
+    let mut field_attrs = FieldAttrs::default();
+    // every option given so far, and the one deciding how the value is printed
+    let mut seen: Vec<&str> = vec![];
+    let mut given: Option<&str> = None;
+    let mut check = |tokens: &dyn quote::ToTokens, name: &'static str, exclusive: bool| {
+        if seen.contains(&name) {
+            return Err(syn::Error::new_spanned(
+                tokens,
+                format!("{} given more than once", name),
+            ));
+        }
+        seen.push(name);
+        match given {
+            Some(prev) if exclusive => Err(syn::Error::new_spanned(
+                tokens,
+                format!("{} cannot be combined with {}", name, prev),
+            )),
+            _ => {
+                if exclusive {
+                    given = Some(name);
+                }
+                Ok(())
+            }
+        }
+    };
+    for attr in attrs.iter().filter(|a| a.path.is_ident("debug")) {
+        match attr.parse_meta()? {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                lit: syn::Lit::Str(lit),
+                ..
+            }) => {
+                check(&lit, "a format string", true)?;
+                field_attrs.format = Some(lit);
             }
-            if let syn::Lit::Str(lt) = lit {
-                return Ok(lt);
+            syn::Meta::List(syn::MetaList { nested, .. }) => {
+                for n in nested {
+                    match n {
+                        syn::NestedMeta::Meta(syn::Meta::Path(ref p)) if p.is_ident("skip") => {
+                            check(&n, "`skip`", true)?;
+                            field_attrs.skip = true;
+                        }
+                        syn::NestedMeta::Meta(syn::Meta::Path(ref p)) if p.is_ident("ptr") => {
+                            check(&n, "`ptr`", true)?;
+                            field_attrs.ptr = true;
+                        }
+                        syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                            ref path,
+                            lit: syn::Lit::Int(ref lit),
+                            ..
+                        })) if path.is_ident("max_len") => {
+                            check(&n, "`max_len`", true)?;
+                            lit.base10_parse::<usize>()?;
+                            field_attrs.max_len = Some(syn::LitInt::new(lit.base10_digits(), lit.span()));
+                        }
+                        syn::NestedMeta::Meta(syn::Meta::Path(ref p)) if p.is_ident("redact") => {
+                            check(&n, "`redact`", true)?;
+                            field_attrs.redact = Some(syn::LitStr::new(
+                                "[REDACTED]",
+                                proc_macro2::Span::call_site(),
+                            ));
+                        }
+                        syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                            ref path,
+                            lit: syn::Lit::Str(ref lit),
+                            ..
+                        })) if path.is_ident("redact") => {
+                            check(&n, "`redact`", true)?;
+                            field_attrs.redact = Some(lit.clone());
+                        }
+                        syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                            ref path,
+                            lit: syn::Lit::Str(ref lit),
+                            ..
+                        })) if path.is_ident("with") => {
+                            check(&n, "`with`", true)?;
+                            field_attrs.with = Some(lit.parse()?);
+                        }
+                        syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                            ref path,
+                            lit: syn::Lit::Str(ref lit),
+                            ..
+                        })) if path.is_ident("bound") => {
+                            check(&n, "`bound`", false)?;
+                            field_attrs.bound = Some(
+                                lit.parse_with(syn::punctuated::Punctuated::parse_terminated)?,
+                            );
+                        }
+                        syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                            ref path,
+                            lit: syn::Lit::Str(ref lit),
+                            ..
+                        })) if path.is_ident("rename") => {
+                            check(&n, "`rename`", false)?;
+                            field_attrs.rename = Some(lit.clone());
+                        }
+                        syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                            ref path,
+                            lit: syn::Lit::Str(ref lit),
+                            ..
+                        })) if path.is_ident("skip_if") => {
+                            check(&n, "`skip_if`", false)?;
+                            field_attrs.skip_if = Some(lit.parse()?);
+                        }
+                        _ => {
+                            return Err(syn::Error::new_spanned(
+                                n,
+                                "expected one of `skip`, `redact`, `with`, `ptr`, `max_len`, `bound`, `rename` or `skip_if`",
+                            ))
+                        }
+                    }
+                }
             }
+            meta => return Err(syn::Error::new_spanned(meta, "expected `debug = \"...\"`")),
         }
-        syn::Meta::List(syn::MetaList {
-            path: syn::Path { segments, .. },
-            nested,
-            ..
-        }) => {
-            if segments[0].ident == "debug" {
-                if let syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
-                    lit: syn::Lit::Str(str),
+    }
+    Ok(field_attrs)
+}
+
+// options collected from `#[debug(...)]` on the struct or enum
+#[derive(Default)]
+struct StructAttrs {
+    // `#[debug(bound = "...")]`
+    bound: Option<syn::punctuated::Punctuated<syn::WherePredicate, syn::Token![,]>>,
+    // `#[debug(rename = "...")]`
+    rename: Option<syn::LitStr>,
+    // `#[debug(transparent)]`
+    transparent: bool,
+    // `#[debug(compact)]`
+    compact: bool,
+    // `#[debug(skip_none)]`
+    skip_none: bool,
+    // `#[debug(raw_bytes)]`
+    raw_bytes: bool,
+    // `#[debug(discriminant)]`
+    discriminant: bool,
+}
+
+fn get_struct_attrs(attrs: &[syn::Attribute]) -> syn::Result<StructAttrs> {
+    let mut struct_attrs = StructAttrs::default();
+    // options that make the others meaningless
+    const CONFLICTS: &[(&str, &str)] = &[
+        ("transparent", "rename"),
+        ("transparent", "skip_none"),
+        ("transparent", "raw_bytes"),
+        ("raw_bytes", "skip_none"),
+        ("discriminant", "transparent"),
+        ("discriminant", "rename"),
+        ("discriminant", "raw_bytes"),
+    ];
+    // every option given so far, across all `#[debug(...)]` attributes
+    let mut seen: Vec<String> = vec![];
+    for attr in attrs.iter().filter(|a| a.path.is_ident("debug")) {
+        let nested = match attr.parse_meta()? {
+            syn::Meta::List(syn::MetaList { nested, .. }) => nested,
+            meta => return Err(syn::Error::new_spanned(meta, "expected `debug(...)`")),
+        };
+        for n in nested {
+            if let syn::NestedMeta::Meta(meta) = &n {
+                let name = meta.path().to_token_stream().to_string();
+                if seen.contains(&name) {
+                    return Err(syn::Error::new_spanned(
+                        &n,
+                        format!("`{}` given more than once", name),
+                    ));
+                }
+                for (a, b) in CONFLICTS {
+                    let other = if name == *a {
+                        b
+                    } else if name == *b {
+                        a
+                    } else {
+                        continue;
+                    };
+                    if seen.iter().any(|s| s == other) {
+                        return Err(syn::Error::new_spanned(
+                            &n,
+                            format!("`{}` cannot be combined with `{}`", name, other),
+                        ));
+                    }
+                }
+                seen.push(name);
+            }
+            match n {
+                syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                    ref path,
+                    lit: syn::Lit::Str(ref lit),
                     ..
-                })) = &nested[0]
-                {
-                    return Ok(str);
+                })) if path.is_ident("bound") => {
+                    struct_attrs.bound =
+                        Some(lit.parse_with(syn::punctuated::Punctuated::parse_terminated)?);
+                }
+                syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                    ref path,
+                    lit: syn::Lit::Str(ref lit),
+                    ..
+                })) if path.is_ident("rename") => struct_attrs.rename = Some(lit.clone()),
+                syn::NestedMeta::Meta(syn::Meta::Path(ref p)) if p.is_ident("transparent") => {
+                    struct_attrs.transparent = true;
+                }
+                syn::NestedMeta::Meta(syn::Meta::Path(ref p)) if p.is_ident("compact") => {
+                    struct_attrs.compact = true;
+                }
+                syn::NestedMeta::Meta(syn::Meta::Path(ref p)) if p.is_ident("skip_none") => {
+                    struct_attrs.skip_none = true;
+                }
+                syn::NestedMeta::Meta(syn::Meta::Path(ref p)) if p.is_ident("raw_bytes") => {
+                    struct_attrs.raw_bytes = true;
+                }
+                syn::NestedMeta::Meta(syn::Meta::Path(ref p)) if p.is_ident("discriminant") => {
+                    struct_attrs.discriminant = true;
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        n,
+                        "expected one of `bound`, `rename`, `transparent`, `compact`, `skip_none`, `raw_bytes` or `discriminant`",
+                    ))
                 }
             }
         }
-        syn::Meta::Path(_) => todo!(),
     }
-    err?
+    Ok(struct_attrs)
+}
+
+fn get_gen_idents(generics: &syn::Generics) -> Vec<syn::Ident> {
+    generics
+        .type_params()
+        .map(|syn::TypeParam { ident, .. }| ident.clone())
+        .collect()
 }
 
-// Add a bound `T: Debug` to every type parameter T.
-// exclude bound in PhantomData
-fn add_trait_bounds(
-    mut generics: syn::Generics,
-    phantom_types: &[&syn::Ident],
-    ass_types: &[&syn::Path],
-) -> syn::Generics {
-    let ass_idents: Vec<_> = ass_types.iter().map(|e| &e.segments[0].ident).collect();
+// Where-predicates of a derived impl: the inferred ones, or the struct's
+// `#[debug(bound = "...")]` replacing all of them, followed by the explicit
+// bounds of single fields.
+fn impl_predicates<'a>(
+    gen_idents: &[syn::Ident],
+    bound: Option<syn::punctuated::Punctuated<syn::WherePredicate, syn::Token![,]>>,
+    types: impl IntoIterator<Item = (&'a syn::Type, syn::Path)>,
+    field_bounds: Vec<syn::WherePredicate>,
+) -> Vec<syn::WherePredicate> {
+    let mut predicates: Vec<syn::WherePredicate> = match bound {
+        // the escape hatch replaces every inferred bound, `bound = ""` means none
+        Some(bound) => bound.into_iter().collect(),
+        None => infer_bounds(gen_idents, types),
+    };
+    predicates.extend(field_bounds);
+    predicates
+}
 
-    for param in &mut generics.params {
-        if let syn::GenericParam::Type(tp) = param {
-            if phantom_types.contains(&&tp.ident) || ass_idents.contains(&&tp.ident) {
-                continue;
+// Where-predicates making each of the field types implement the paired trait,
+// e.g. `T: Debug` for a field of type `Vec<T>`.
+fn infer_bounds<'a>(
+    gen_idents: &[syn::Ident],
+    types: impl IntoIterator<Item = (&'a syn::Type, syn::Path)>,
+) -> Vec<syn::WherePredicate> {
+    let mut predicates: Vec<syn::WherePredicate> = vec![];
+    for (ty, bound) in types {
+        let mut visitor = BoundVisitor {
+            gen_idents,
+            bounded_params: vec![],
+            predicates: vec![],
+        };
+        syn::visit::Visit::visit_type(&mut visitor, ty);
+        let params = visitor
+            .bounded_params
+            .iter()
+            .map(|p| -> syn::WherePredicate { syn::parse_quote!(#p: #bound) });
+        let others = visitor
+            .predicates
+            .iter()
+            .map(|t| -> syn::WherePredicate { syn::parse_quote!(#t: #bound) });
+        for predicate in params.chain(others) {
+            let tokens = quote!(#predicate).to_string();
+            if !predicates.iter().any(|p| quote!(#p).to_string() == tokens) {
+                predicates.push(predicate);
             }
         }
-        if let syn::GenericParam::Type(ref mut type_param) = *param {
-            type_param.bounds.push(syn::parse_quote!(std::fmt::Debug));
+    }
+    predicates
+}
+
+// Walks a field type and collects what has to implement a formatting trait for
+// the field to implement it. Generic types are assumed to implement it when
+// their type arguments do, so their arguments are walked rather than bounding
+// the whole type, which would leak private types and cause cycles with
+// recursive types.
+struct BoundVisitor<'a> {
+    gen_idents: &'a [syn::Ident],
+    // params that need `T: Trait`
+    bounded_params: Vec<&'a syn::Ident>,
+    // other types that need `Type: Trait`, such as `T::Value`
+    predicates: Vec<syn::Type>,
+}
+
+impl<'a> BoundVisitor<'a> {
+    fn mentions_param(&self, ty: &syn::Type) -> bool {
+        self.gen_idents
+            .iter()
+            .any(|ident| mentions_ident(quote!(#ty), ident))
+    }
+
+    fn add_predicate(&mut self, ty: &syn::Type) {
+        let tokens = quote!(#ty).to_string();
+        if !self
+            .predicates
+            .iter()
+            .any(|p| quote!(#p).to_string() == tokens)
+        {
+            self.predicates.push(ty.clone());
         }
     }
-    generics
+}
+
+impl<'a, 'ast> syn::visit::Visit<'ast> for BoundVisitor<'a> {
+    fn visit_type(&mut self, ty: &'ast syn::Type) {
+        if !self.mentions_param(ty) {
+            return;
+        }
+        match ty {
+            // Debug for any T
+            syn::Type::Ptr(_) | syn::Type::BareFn(_) => {}
+            _ if get_inner(Some("PhantomData"), ty).is_some() => {}
+            syn::Type::Path(syn::TypePath { qself: None, path }) => {
+                let first = &path.segments[0];
+                match self.gen_idents.iter().find(|i| **i == first.ident) {
+                    // `T`
+                    Some(param) if path.segments.len() == 1 && first.arguments.is_empty() => {
+                        if !self.bounded_params.contains(&param) {
+                            self.bounded_params.push(param);
+                        }
+                    }
+                    // `T::Value`
+                    Some(_) => self.add_predicate(ty),
+                    None => syn::visit::visit_type(self, ty),
+                }
+            }
+            // `<T as Trait>::Value`, `dyn Trait<T>`, `impl Trait<T>`, macros...
+            syn::Type::Path(_)
+            | syn::Type::TraitObject(_)
+            | syn::Type::ImplTrait(_)
+            | syn::Type::Macro(_)
+            | syn::Type::Verbatim(_) => self.add_predicate(ty),
+            // tuples, arrays, slices, references, parens
+            _ => syn::visit::visit_type(self, ty),
+        }
+    }
+}
+
+fn mentions_ident(tokens: proc_macro2::TokenStream, ident: &syn::Ident) -> bool {
+    tokens.into_iter().any(|tt| match tt {
+        proc_macro2::TokenTree::Ident(i) => i == *ident,
+        proc_macro2::TokenTree::Group(g) => mentions_ident(g.stream(), ident),
+        _ => false,
+    })
 }
 
 fn get_inner<'a>(wrapper: Option<&str>, ty: &'a syn::Type) -> Option<&'a syn::Type> {
@@ -188,19 +1057,3 @@ fn get_inner<'a>(wrapper: Option<&str>, ty: &'a syn::Type) -> Option<&'a syn::Ty
     }
     None
 }
-
-// check if first segment of ty in included in Vec of trait
-fn get_assosiate_type<'a>(
-    ty: &'a syn::Type,
-    gen_idents: &Vec<syn::Ident>,
-) -> Option<&'a syn::Path> {
-    if let Some(inner) = get_inner(None, ty) {
-        return get_assosiate_type(inner, gen_idents);
-    };
-    if let syn::Type::Path(syn::TypePath { path, .. }) = ty {
-        if path.segments.len() > 1 && gen_idents.contains(&path.segments[0].ident) {
-            return Some(path);
-        }
-    }
-    None
-}
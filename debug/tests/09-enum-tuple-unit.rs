@@ -0,0 +1,48 @@
+// Besides structs with named fields, CustomDebug handles tuple structs, unit
+// structs and enums, producing the same output as the built-in derive:
+// `debug_tuple` for tuple structs and variants, `debug_struct` for named
+// fields, and just the name for unit structs and unit variants.
+//
+// The #[debug = "..."] format attribute works on the fields of enum variants
+// and tuple structs too.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Unit;
+
+#[derive(CustomDebug)]
+pub struct Pair(u8, #[debug = "0x{:02x}"] u8);
+
+#[derive(CustomDebug)]
+pub enum Shape<T> {
+    Empty,
+    Circle(T),
+    Rect {
+        width: T,
+        #[debug = "0b{:08b}"]
+        flags: u8,
+    },
+}
+
+#[derive(CustomDebug)]
+pub enum Never {}
+
+fn main() {
+    assert_eq!(format!("{:?}", Unit), "Unit");
+    assert_eq!(format!("{:?}", Pair(1, 255)), "Pair(1, 0xff)");
+
+    assert_eq!(format!("{:?}", Shape::<u32>::Empty), "Empty");
+    assert_eq!(format!("{:?}", Shape::Circle(2.5)), "Circle(2.5)");
+    assert_eq!(
+        format!("{:?}", Shape::Rect { width: 3, flags: 5 }),
+        "Rect { width: 3, flags: 0b00000101 }",
+    );
+    assert_eq!(
+        format!("{:#?}", Shape::Circle("r")),
+        "Circle(\n    \"r\",\n)",
+    );
+
+    fn assert_debug<F: std::fmt::Debug>() {}
+    assert_debug::<Never>();
+}
@@ -0,0 +1,57 @@
+// Some fields must never show up in logs. A field marked #[debug(skip)] is left
+// out of the output entirely, and the struct is printed with a trailing `..` to
+// show that something was omitted. A field marked #[debug(redact)] stays in the
+// output but its value is replaced by a placeholder, which can be customized
+// with #[debug(redact = "...")].
+
+use derive_debug::CustomDebug;
+
+pub struct Secret(String);
+
+#[derive(CustomDebug)]
+pub struct Login {
+    user: &'static str,
+    #[debug(redact)]
+    password: &'static str,
+    #[debug(redact = "***")]
+    token: u64,
+    #[debug(skip)]
+    secret: Secret,
+}
+
+#[derive(CustomDebug)]
+pub enum Credential {
+    Key(#[debug(skip)] Secret),
+    Basic {
+        user: &'static str,
+        #[debug(redact = "<hidden>")]
+        password: &'static str,
+    },
+}
+
+fn main() {
+    let login = Login {
+        user: "root",
+        password: "hunter2",
+        token: 42,
+        secret: Secret("s3cr3t".to_owned()),
+    };
+    assert_eq!(
+        format!("{:?}", login),
+        r#"Login { user: "root", password: [REDACTED], token: ***, .. }"#,
+    );
+
+    let key = Credential::Key(Secret("s3cr3t".to_owned()));
+    assert_eq!(format!("{:?}", key), "Key(..)");
+
+    let basic = Credential::Basic {
+        user: "admin",
+        password: "hunter2",
+    };
+    assert_eq!(
+        format!("{:?}", basic),
+        r#"Basic { user: "admin", password: <hidden> }"#,
+    );
+
+    let _ = (&login.secret.0, &login.password, &login.token);
+}
@@ -0,0 +1,14 @@
+// A format string would be ignored on a redacted or skipped field, which is
+// almost certainly a mistake, so the combination is rejected.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Login {
+    user: &'static str,
+    #[debug = "{:?}"]
+    #[debug(redact)]
+    password: &'static str,
+}
+
+fn main() {}
@@ -0,0 +1,58 @@
+// For fields where a format string isn't enough, #[debug(with = "...")] names
+// a function with the signature `fn(&T, &mut fmt::Formatter) -> fmt::Result`
+// that is called to format the field, where T is the field's type. Generic
+// functions work as well.
+
+use derive_debug::CustomDebug;
+use std::fmt;
+use std::time::Duration;
+
+fn hex_dump(bytes: &Vec<u8>, f: &mut fmt::Formatter) -> fmt::Result {
+    for b in bytes {
+        write!(f, "{:02x}", b)?;
+    }
+    Ok(())
+}
+
+mod fmt_util {
+    use std::fmt;
+    use std::time::Duration;
+
+    pub fn millis(d: &Duration, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}ms", d.as_millis())
+    }
+}
+
+fn length<T: AsRef<[u8]>>(v: &T, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "<{} bytes>", v.as_ref().len())
+}
+
+#[derive(CustomDebug)]
+pub struct Packet<T: AsRef<[u8]>> {
+    #[debug(with = "hex_dump")]
+    header: Vec<u8>,
+    #[debug(with = "fmt_util::millis")]
+    elapsed: Duration,
+    #[debug(with = "length")]
+    payload: T,
+}
+
+#[derive(CustomDebug)]
+pub enum Frame {
+    Data(#[debug(with = "hex_dump")] Vec<u8>),
+}
+
+fn main() {
+    let packet = Packet {
+        header: vec![0xde, 0xad, 0xbe, 0xef],
+        elapsed: Duration::from_millis(1500),
+        payload: "hello",
+    };
+    assert_eq!(
+        format!("{:?}", packet),
+        "Packet { header: deadbeef, elapsed: 1500ms, payload: <5 bytes> }",
+    );
+
+    let frame = Frame::Data(vec![1, 2]);
+    assert_eq!(format!("{:?}", frame), "Data(0102)");
+}
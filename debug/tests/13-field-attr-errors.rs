@@ -0,0 +1,31 @@
+// Mistakes in a field's #[debug ...] attribute are reported as compile errors
+// pointing at the offending part of the attribute, rather than crashing the
+// macro.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct UnknownOption {
+    #[debug(skp)]
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct BarePath {
+    #[debug]
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct NotAString {
+    #[debug = 8]
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct BadFormatter {
+    #[debug(with = "not a path")]
+    value: u8,
+}
+
+fn main() {}
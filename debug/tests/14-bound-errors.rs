@@ -0,0 +1,25 @@
+// Mistakes in the struct-level #[debug(bound = "...")] escape hatch are
+// reported as compile errors as well, including a bound that does not parse as
+// a where-predicate.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(bond = "T: Debug")]
+pub struct UnknownOption<T> {
+    value: T,
+}
+
+#[derive(CustomDebug)]
+#[debug]
+pub struct BarePath<T> {
+    value: T,
+}
+
+#[derive(CustomDebug)]
+#[debug(bound = "T::: Debug")]
+pub struct BadPredicate<T> {
+    value: T,
+}
+
+fn main() {}
@@ -0,0 +1,68 @@
+// The struct-level escape hatch accepts a comma-separated list of
+// where-predicates, and an empty string to mean no bounds at all.
+//
+// A field-level #[debug(bound = "...")] substitutes only the bounds that would
+// have been inferred from that field's type, while bounds inferred from the
+// other fields are kept:
+//
+//     impl<T: Trait, U: Debug> Debug for Wrapper<T, U>
+//     where
+//         T::Value: Debug,
+//     {...}
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+pub trait Trait {
+    type Value;
+}
+
+#[derive(CustomDebug)]
+struct Field<T: Trait> {
+    values: Vec<T::Value>,
+}
+
+#[derive(CustomDebug)]
+pub struct Wrapper<T: Trait, U> {
+    #[debug(bound = "T::Value: Debug")]
+    field: Field<T>,
+    normal: U,
+}
+
+#[derive(CustomDebug)]
+#[debug(bound = "T::Value: Debug, U: Debug,")]
+pub struct Pair<T: Trait, U> {
+    field: Field<T>,
+    normal: U,
+}
+
+#[derive(CustomDebug)]
+#[debug(bound = "")]
+pub struct Unbounded<T> {
+    marker: PhantomData<fn(T)>,
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    struct Id;
+    struct NotDebug;
+
+    impl Trait for Id {
+        type Value = u8;
+    }
+
+    assert_debug::<Wrapper<Id, u8>>();
+    assert_debug::<Pair<Id, String>>();
+    assert_debug::<Unbounded<NotDebug>>();
+
+    let wrapper = Wrapper::<Id, _> {
+        field: Field { values: vec![1] },
+        normal: "n",
+    };
+    assert_eq!(
+        format!("{:?}", wrapper),
+        r#"Wrapper { field: Field { values: [1] }, normal: "n" }"#,
+    );
+}
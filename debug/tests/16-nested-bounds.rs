@@ -0,0 +1,49 @@
+// Bounds are inferred by walking the whole type of each field. Type parameters
+// anywhere inside generic arguments, tuples, arrays, slices and references are
+// bounded, associated types like T::Item are bounded wherever they appear, and
+// qualified paths or trait objects that mention a parameter are bounded as a
+// whole:
+//
+//     impl<'a, K: Debug, T: Iterator, U: Debug, V: Trait, W> Debug for Nested<'a, K, T, U, V, W>
+//     where
+//         T::Item: Debug,
+//         <V as Trait>::Value: Debug,
+//     {...}
+//
+// Types whose Debug impl holds for any parameter, namely PhantomData, raw
+// pointers and fn pointers, don't require a bound. Neither do fields that are
+// not formatted through their own Debug impl, such as skipped fields.
+
+use derive_debug::CustomDebug;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+pub trait Trait {
+    type Value;
+}
+
+#[derive(CustomDebug)]
+pub struct Nested<'a, K, T: Iterator, U, V: Trait, W> {
+    map: HashMap<K, Vec<T::Item>>,
+    pair: (U, [Option<U>; 2]),
+    slice: &'a [U],
+    marker: Option<PhantomData<V>>,
+    ptr: *const W,
+    callback: fn(W) -> W,
+    value: <V as Trait>::Value,
+    #[debug(skip)]
+    hidden: Option<W>,
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    struct NotDebug;
+
+    impl Trait for NotDebug {
+        type Value = u8;
+    }
+
+    assert_debug::<Nested<String, std::vec::IntoIter<u8>, i32, NotDebug, NotDebug>>();
+}
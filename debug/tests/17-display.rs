@@ -0,0 +1,94 @@
+// CustomDisplay derives std::fmt::Display from a format string given in a
+// #[display("...")] attribute on the struct, or on each variant of an enum.
+// Fields are referenced by name, or by index for tuple fields, and may use any
+// format spec:
+//
+//     #[derive(CustomDisplay)]
+//     #[display("{name} at {addr:?}")]
+//     pub struct Peer {...}
+//
+// Trait bounds are inferred the same way as for CustomDebug, for the traits the
+// format string actually uses: `{value}` requires Display, `{value:?}` requires
+// Debug, `{value:x}` requires LowerHex and so on. Fields not mentioned in the
+// format string don't need to implement anything.
+
+use derive_debug::{CustomDebug, CustomDisplay};
+use std::fmt::Display;
+
+#[derive(CustomDisplay)]
+#[display("{name} at {addr:?}")]
+pub struct Peer {
+    name: String,
+    addr: (u8, u8, u8, u8),
+}
+
+#[derive(CustomDisplay)]
+#[display("{0}/{1:#x}")]
+pub struct Pair<T>(T, u32);
+
+#[derive(CustomDisplay)]
+#[display("{{{value:>4}}}")]
+pub struct Padded<T, U> {
+    value: T,
+    unused: U,
+}
+
+#[derive(CustomDebug, CustomDisplay)]
+pub enum Event<T> {
+    #[display("started")]
+    Started,
+    #[display("progress {0}%")]
+    Progress(u8),
+    #[display("failed with {code}: {reason:?}")]
+    Failed { code: i32, reason: T },
+}
+
+#[derive(CustomDisplay)]
+#[display("empty")]
+pub struct Empty {}
+
+#[derive(CustomDisplay)]
+#[display("nothing")]
+pub struct Nothing();
+
+#[derive(CustomDisplay)]
+pub enum Shape {
+    #[display("braces")]
+    Braces {},
+    #[display("parens")]
+    Parens(),
+}
+
+fn assert_display<F: Display>() {}
+
+fn main() {
+    let peer = Peer {
+        name: "node-1".to_owned(),
+        addr: (10, 0, 0, 1),
+    };
+    assert_eq!(peer.to_string(), "node-1 at (10, 0, 0, 1)");
+
+    assert_eq!(Pair("x", 255).to_string(), "x/0xff");
+
+    struct NotDisplay;
+    let padded = Padded {
+        value: 7,
+        unused: NotDisplay,
+    };
+    assert_eq!(padded.to_string(), "{   7}");
+    assert_display::<Padded<u8, NotDisplay>>();
+
+    assert_eq!(Event::<()>::Started.to_string(), "started");
+    assert_eq!(Event::<()>::Progress(40).to_string(), "progress 40%");
+    let failed = Event::Failed {
+        code: 2,
+        reason: "timeout",
+    };
+    assert_eq!(failed.to_string(), r#"failed with 2: "timeout""#);
+    assert_eq!(format!("{:?}", failed), r#"Failed { code: 2, reason: "timeout" }"#);
+
+    assert_eq!(Empty {}.to_string(), "empty");
+    assert_eq!(Nothing().to_string(), "nothing");
+    assert_eq!(Shape::Braces {}.to_string(), "braces");
+    assert_eq!(Shape::Parens().to_string(), "parens");
+}
@@ -0,0 +1,13 @@
+// Every argument of a #[display("...")] format string has to name a field of
+// the struct or variant.
+
+use derive_debug::CustomDisplay;
+
+#[derive(CustomDisplay)]
+#[display("{name} at {address}")]
+pub struct Peer {
+    name: String,
+    addr: String,
+}
+
+fn main() {}
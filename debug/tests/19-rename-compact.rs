@@ -0,0 +1,78 @@
+// Output can be reshaped to keep log lines short:
+//
+//   - #[debug(rename = "...")] on the struct replaces the struct name, and on a
+//     field replaces the field name.
+//
+//   - #[debug(transparent)] on a newtype prints it exactly like its only field,
+//     so `UserId(7)` shows up as just `7`.
+//
+//   - #[debug(compact)] keeps the struct and everything nested in it on one
+//     line, even when formatted with {:#?}.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct UserId(u64);
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct SessionId(UserId);
+
+#[derive(CustomDebug)]
+#[debug(rename = "Req")]
+pub struct Request {
+    #[debug(rename = "id")]
+    session: SessionId,
+    path: &'static str,
+}
+
+#[derive(CustomDebug)]
+#[debug(compact)]
+pub struct Point {
+    x: i32,
+    y: i32,
+    tags: Vec<&'static str>,
+}
+
+#[derive(CustomDebug)]
+pub struct Line {
+    from: Point,
+    to: Point,
+}
+
+#[derive(CustomDebug)]
+#[debug(compact)]
+pub enum Shape {
+    Dot(Point),
+}
+
+fn main() {
+    let request = Request {
+        session: SessionId(UserId(7)),
+        path: "/",
+    };
+    assert_eq!(format!("{:?}", request), r#"Req { id: UserId(7), path: "/" }"#);
+
+    let point = Point {
+        x: 1,
+        y: 2,
+        tags: vec!["a"],
+    };
+    assert_eq!(format!("{:#?}", point), r#"Point { x: 1, y: 2, tags: ["a"] }"#);
+
+    let line = Line {
+        from: Point {
+            x: 0,
+            y: 0,
+            tags: vec![],
+        },
+        to: point,
+    };
+    assert_eq!(
+        format!("{:#?}", line),
+        "Line {\n    from: Point { x: 0, y: 0, tags: [] },\n    to: Point { x: 1, y: 2, tags: [\"a\"] },\n}",
+    );
+
+    let dot = Shape::Dot(line.to);
+    assert_eq!(format!("{:#?}", dot), r#"Dot(Point { x: 1, y: 2, tags: ["a"] })"#);
+}
@@ -0,0 +1,80 @@
+// Optional and empty fields mostly add noise to a debug line. They can be left
+// out whenever they carry nothing:
+//
+//   - #[debug(skip_if = "path::to::fn")] on a field calls the function with a
+//     reference to the field and omits the field when it returns true.
+//
+//   - #[debug(skip_none)] on the struct or enum omits every `Option` field
+//     holding `None` and every standard collection that is empty.
+//
+// Unlike #[debug(skip)], fields omitted at runtime do not print a trailing
+// `..`, since nothing is hidden from the reader.
+
+use derive_debug::CustomDebug;
+use std::collections::HashMap;
+
+fn is_zero(n: &u32) -> bool {
+    *n == 0
+}
+
+#[derive(CustomDebug)]
+pub struct Retry {
+    #[debug(skip_if = "is_zero")]
+    attempts: u32,
+    #[debug(skip_if = "Option::is_none")]
+    last_error: Option<&'static str>,
+}
+
+#[derive(CustomDebug)]
+#[debug(skip_none)]
+pub struct Request {
+    path: &'static str,
+    query: Option<&'static str>,
+    headers: HashMap<&'static str, &'static str>,
+    body: Vec<u8>,
+}
+
+#[derive(CustomDebug)]
+#[debug(skip_none)]
+pub enum Event {
+    Click(u32, Option<u32>),
+}
+
+fn main() {
+    let retry = Retry {
+        attempts: 0,
+        last_error: None,
+    };
+    assert_eq!(format!("{:?}", retry), "Retry");
+
+    let retry = Retry {
+        attempts: 2,
+        last_error: Some("timeout"),
+    };
+    assert_eq!(
+        format!("{:?}", retry),
+        r#"Retry { attempts: 2, last_error: Some("timeout") }"#,
+    );
+
+    let request = Request {
+        path: "/",
+        query: None,
+        headers: HashMap::new(),
+        body: vec![],
+    };
+    assert_eq!(format!("{:?}", request), r#"Request { path: "/" }"#);
+
+    let request = Request {
+        path: "/search",
+        query: Some("q=1"),
+        headers: HashMap::new(),
+        body: vec![1],
+    };
+    assert_eq!(
+        format!("{:?}", request),
+        r#"Request { path: "/search", query: Some("q=1"), body: [1] }"#,
+    );
+
+    assert_eq!(format!("{:?}", Event::Click(1, None)), "Click(1)");
+    assert_eq!(format!("{:?}", Event::Click(1, Some(2))), "Click(1, Some(2))");
+}
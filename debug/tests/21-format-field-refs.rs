@@ -0,0 +1,94 @@
+// A #[debug = "..."] format string can refer to the other fields of the same
+// struct or variant by name, either as `{unit}` or as `{self.unit}`, so a field
+// can be shown together with its context. Positional `{}` placeholders keep
+// referring to the annotated field itself.
+//
+// Referenced fields pick up the trait their placeholder asks for, so with
+// generics `{self.unit}` requires `U: Display` and `{unit:?}` requires
+// `U: Debug`. The annotated field is bounded the same way by its positional
+// placeholders, `{:x}` requiring `T: LowerHex`, and needs nothing when the
+// format string doesn't print it at all.
+
+use derive_debug::CustomDebug;
+use std::fmt::Display;
+
+#[derive(CustomDebug)]
+pub struct Measurement {
+    #[debug = "{self.value} {unit}"]
+    value: f64,
+    unit: &'static str,
+}
+
+#[derive(CustomDebug)]
+pub struct Reading<U> {
+    #[debug = "{:.1} {self.unit}"]
+    value: f64,
+    unit: U,
+}
+
+#[derive(CustomDebug)]
+pub struct Hexy<T> {
+    #[debug = "{:x}"]
+    value: T,
+}
+
+pub struct NotDebug;
+
+#[derive(CustomDebug)]
+pub struct Labelled<T> {
+    #[debug = "{label}"]
+    value: T,
+    label: &'static str,
+}
+
+#[derive(CustomDebug)]
+pub enum Limit {
+    Max(#[debug = "<= {} {self.1}"] u32, &'static str),
+}
+
+pub struct Celsius;
+
+impl Display for Celsius {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("°C")
+    }
+}
+
+impl std::fmt::Debug for Celsius {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("Celsius")
+    }
+}
+
+fn main() {
+    let m = Measurement {
+        value: 1.5,
+        unit: "kg",
+    };
+    assert_eq!(
+        format!("{:?}", m),
+        r#"Measurement { value: 1.5 kg, unit: "kg" }"#,
+    );
+
+    let r = Reading {
+        value: 21.04,
+        unit: Celsius,
+    };
+    assert_eq!(
+        format!("{:?}", r),
+        "Reading { value: 21.0 °C, unit: Celsius }",
+    );
+
+    assert_eq!(format!("{:?}", Limit::Max(3, "s")), r#"Max(<= 3 s, "s")"#);
+
+    assert_eq!(format!("{:?}", Hexy { value: 255u8 }), "Hexy { value: ff }");
+
+    let labelled = Labelled {
+        value: NotDebug,
+        label: "opaque",
+    };
+    assert_eq!(
+        format!("{:?}", labelled),
+        r#"Labelled { value: opaque, label: "opaque" }"#,
+    );
+}
@@ -0,0 +1,13 @@
+// Every field a #[debug = "..."] format string refers to by name has to exist
+// in the same struct or variant.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Measurement {
+    #[debug = "{} {self.units}"]
+    value: f64,
+    unit: &'static str,
+}
+
+fn main() {}
@@ -0,0 +1,51 @@
+// Unions print as `Name { .. }`, since which of their fields holds a valid
+// value is only known to the surrounding code.
+//
+// With #[debug(raw_bytes)], structs and unions print the bytes of their value
+// in hex instead, which is handy for register snapshots and other FFI data.
+// This requires the type to be `Copy`, to have its layout fixed by
+// #[repr(...)], and to have no padding: the fields are integers, floats, bools
+// or arrays of them, and together they cover every byte of the value.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug, Clone, Copy)]
+#[repr(C)]
+pub union Value {
+    int: u32,
+    float: f32,
+}
+
+#[derive(CustomDebug, Clone, Copy)]
+#[debug(raw_bytes)]
+#[repr(C)]
+pub union Register {
+    word: u32,
+    bytes: [u8; 4],
+}
+
+#[derive(CustomDebug, Clone, Copy)]
+#[debug(raw_bytes)]
+#[repr(C)]
+pub struct Status {
+    flags: u8,
+    code: u8,
+    len: u16,
+}
+
+fn main() {
+    let value = Value { int: 1 };
+    assert_eq!(format!("{:?}", value), "Value { .. }");
+
+    let register = Register {
+        bytes: [0x01, 0x00, 0xff, 0x7f],
+    };
+    assert_eq!(format!("{:?}", register), "Register(01 00 ff 7f)");
+
+    let status = Status {
+        flags: 0x80,
+        code: 0x0a,
+        len: 0,
+    };
+    assert_eq!(format!("{:?}", status), "Status(80 0a 00 00)");
+}
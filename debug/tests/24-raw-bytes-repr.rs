@@ -0,0 +1,21 @@
+// Without #[repr(...)] the compiler is free to reorder fields, so
+// #[debug(raw_bytes)] is rejected. Fields other than integers, floats, bools
+// and arrays of them may contain padding or pointers and are rejected too.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug, Clone, Copy)]
+#[debug(raw_bytes)]
+pub struct Status {
+    flags: u8,
+    len: u16,
+}
+
+#[derive(CustomDebug, Clone, Copy)]
+#[debug(raw_bytes)]
+#[repr(C)]
+pub struct Nested {
+    pair: (u8, u32),
+}
+
+fn main() {}
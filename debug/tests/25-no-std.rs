@@ -0,0 +1,39 @@
+// The generated impls only use paths from `core`, so CustomDebug works in
+// #![no_std] crates. The standard library is still linked under another name
+// to run the test, which keeps `::std` paths from resolving.
+
+#![no_std]
+
+extern crate std as host;
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(compact)]
+pub struct Frame<T> {
+    #[debug = "{:#04x}"]
+    id: u8,
+    #[debug(skip_if = "Option::is_none")]
+    payload: Option<T>,
+    #[debug(redact)]
+    key: [u8; 4],
+}
+
+#[derive(CustomDebug)]
+pub enum Event {
+    Reset,
+    Frame(Frame<u16>),
+}
+
+fn main() {
+    let frame = Frame {
+        id: 7,
+        payload: Some(1u16),
+        key: [0; 4],
+    };
+    host::assert_eq!(
+        host::format!("{:#?}", frame),
+        r#"Frame { id: 0x07, payload: Some(1), key: [REDACTED] }"#,
+    );
+    host::assert_eq!(host::format!("{:?}", Event::Reset), "Reset");
+}
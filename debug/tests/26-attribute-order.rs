@@ -0,0 +1,30 @@
+// #[debug(...)] attributes are found wherever they are placed among other
+// attributes such as doc comments or #[allow], and several of them on the same
+// item are merged as if they had been written as one.
+
+use derive_debug::CustomDebug;
+
+/// A user of the service.
+#[derive(CustomDebug)]
+#[allow(dead_code)]
+#[debug(rename = "U")]
+#[debug(skip_none)]
+pub struct User {
+    /// Unique id.
+    #[debug = "#{}"]
+    id: u32,
+    #[allow(dead_code)]
+    #[debug(rename = "pw")]
+    #[debug(redact)]
+    password: String,
+    nickname: Option<String>,
+}
+
+fn main() {
+    let user = User {
+        id: 7,
+        password: "hunter2".to_owned(),
+        nickname: None,
+    };
+    assert_eq!(format!("{:?}", user), "U { id: #7, pw: [REDACTED] }");
+}
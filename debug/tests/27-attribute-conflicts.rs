@@ -0,0 +1,25 @@
+// Options given more than once, or options that contradict each other, are
+// reported even when they are spread over several #[debug(...)] attributes.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(rename = "A")]
+#[debug(rename = "B")]
+pub struct Renamed {
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+#[debug(skip_none)]
+pub struct Wrapper(Option<u8>);
+
+#[derive(CustomDebug)]
+pub struct Field {
+    #[debug(rename = "a")]
+    #[debug(rename = "b")]
+    value: u8,
+}
+
+fn main() {}
@@ -0,0 +1,56 @@
+// Large buffers flood the logs when printed in full:
+//
+//   - #[debug(max_len = N)] prints at most N items of a collection, followed by
+//     the number of items left out. References, `Box`, `Arc` and `Rc` around
+//     the collection are looked through, and so is a `Mutex` or `RwLock`,
+//     which is locked without blocking and printed as `<locked>` while
+//     someone else holds it.
+//
+//   - #[debug(ptr)] prints the address a `Box`, `Arc`, `Rc`, reference or raw
+//     pointer points to instead of its contents.
+
+use derive_debug::CustomDebug;
+use std::sync::{Arc, Mutex, RwLock};
+
+#[derive(CustomDebug)]
+pub struct Packet<'a> {
+    #[debug(max_len = 4)]
+    payload: Vec<u8>,
+    #[debug(max_len = 2)]
+    shared: Arc<[u16]>,
+    #[debug(max_len = 8)]
+    header: &'a [u8],
+    #[debug(max_len = 3)]
+    pending: Arc<Mutex<Vec<u8>>>,
+    #[debug(max_len = 1)]
+    routes: RwLock<Box<[u32]>>,
+    #[debug(ptr)]
+    buffer: Box<[u8; 1024]>,
+    #[debug(ptr)]
+    raw: *const u8,
+}
+
+fn main() {
+    let buffer = Box::new([0; 1024]);
+    let address = format!("{:p}", buffer);
+    let packet = Packet {
+        payload: (0..10).collect(),
+        shared: Arc::from(&[1, 2, 3][..]),
+        header: &[7, 7],
+        pending: Arc::new(Mutex::new(vec![5; 5])),
+        routes: RwLock::new(Box::new([80, 443])),
+        buffer,
+        raw: std::ptr::null(),
+    };
+    assert_eq!(
+        format!("{:?}", packet),
+        format!(
+            "Packet {{ payload: [0, 1, 2, 3, ..; 6 more], shared: [1, 2, ..; 1 more], header: [7, 7], pending: [5, 5, 5, ..; 2 more], routes: [80, ..; 1 more], buffer: {}, raw: 0x0 }}",
+            address,
+        ),
+    );
+
+    let pending = packet.pending.clone();
+    let _guard = pending.lock().unwrap();
+    assert!(format!("{:?}", packet).contains("pending: <locked>, routes: [80, ..; 1 more]"));
+}
@@ -0,0 +1,37 @@
+// C-like enums can show their numeric value next to the variant name with
+// #[debug(discriminant)] on the enum, printing `Level::Warn = 2`. Variants
+// without an explicit discriminant count up from the previous one, and the
+// value has the type given by #[repr(...)].
+//
+// Only enums whose variants all are unit variants are accepted.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(discriminant)]
+pub enum Level {
+    Trace,
+    Debug,
+    Warn = 4,
+    Error,
+}
+
+const BASE: u8 = 0x80;
+
+#[derive(CustomDebug)]
+#[debug(discriminant)]
+#[repr(u8)]
+pub enum Opcode {
+    Nop = BASE,
+    Halt,
+}
+
+fn main() {
+    assert_eq!(format!("{:?}", Level::Trace), "Level::Trace = 0");
+    assert_eq!(format!("{:?}", Level::Debug), "Level::Debug = 1");
+    assert_eq!(format!("{:?}", Level::Warn), "Level::Warn = 4");
+    assert_eq!(format!("{:?}", Level::Error), "Level::Error = 5");
+
+    assert_eq!(format!("{:?}", Opcode::Halt), "Opcode::Halt = 129");
+    assert_eq!(format!("{:?}", Opcode::Halt as u8), "129");
+}
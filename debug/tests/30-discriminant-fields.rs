@@ -0,0 +1,12 @@
+// Variants carrying data have no numeric value to show.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(discriminant)]
+pub enum Message {
+    Ping,
+    Data(Vec<u8>),
+}
+
+fn main() {}
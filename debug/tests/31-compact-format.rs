@@ -0,0 +1,118 @@
+// CustomFormat derives a compact binary encoding for deferred-formatting
+// loggers. Instead of the formatted text, `write` emits the id of an interned
+// format string followed by the encoded fields, and the host side turns it
+// back into text using the table in `Type::COMPACT_FORMATS`.
+//
+// The derive implements a `CompactFormat` trait with this signature, which is
+// looked up where the derive is used together with the `Write` trait:
+//
+//     pub trait CompactFormat {
+//         fn write(&self, w: &mut impl Write);
+//     }
+//
+// Ids are written as `u32`, so the logger implements CompactFormat for `u32`
+// and for whatever else its fields use. Format strings take the same shape as
+// the CustomDebug output and the same #[debug(...)] attributes apply: skipped
+// fields show up as `..`, redacted fields are not written at all and their
+// placeholder goes into the format string, and renames and format strings
+// change the text. A field is written once for every positional placeholder of
+// its format string, `{0} ({0:#x})` becoming `{} ({:#x})` with two writes.
+
+use derive_debug::{CustomDebug, CustomFormat};
+
+pub trait Write {
+    fn write_bytes(&mut self, bytes: &[u8]);
+}
+
+impl Write for Vec<u8> {
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        self.extend_from_slice(bytes);
+    }
+}
+
+pub trait CompactFormat {
+    fn write(&self, w: &mut impl Write);
+}
+
+impl CompactFormat for u32 {
+    fn write(&self, w: &mut impl Write) {
+        w.write_bytes(&self.to_le_bytes());
+    }
+}
+
+impl CompactFormat for u8 {
+    fn write(&self, w: &mut impl Write) {
+        w.write_bytes(&[*self]);
+    }
+}
+
+#[derive(CustomDebug, CustomFormat)]
+pub struct Sample<T> {
+    #[debug = "{:#x}"]
+    channel: u8,
+    value: T,
+    #[debug(skip)]
+    cache: Vec<u8>,
+}
+
+#[derive(CustomDebug, CustomFormat)]
+pub enum Event {
+    Boot,
+    Login {
+        user: u32,
+        #[debug(redact)]
+        pin: u32,
+    },
+}
+
+#[derive(CustomDebug, CustomFormat)]
+pub struct Status {
+    #[debug = "{0} ({0:#x})"]
+    code: u8,
+}
+
+fn id(table: &[(u32, &str)], format: &str) -> [u8; 4] {
+    let (id, _) = table.iter().find(|(_, f)| *f == format).unwrap();
+    id.to_le_bytes()
+}
+
+fn main() {
+    let sample = Sample {
+        channel: 2,
+        value: 7u32,
+        cache: vec![],
+    };
+    assert_eq!(
+        Sample::<u32>::COMPACT_FORMATS[0].1,
+        "Sample {{ channel: {:#x}, value: {}, .. }}",
+    );
+    let mut buf = vec![];
+    sample.write(&mut buf);
+    let mut expected = Sample::<u32>::COMPACT_FORMATS[0].0.to_le_bytes().to_vec();
+    expected.extend([2, 7, 0, 0, 0]);
+    assert_eq!(buf, expected);
+    assert_eq!(format!("{:?}", sample), "Sample { channel: 0x2, value: 7, .. }");
+
+    let formats = Event::COMPACT_FORMATS;
+    assert_eq!(formats.len(), 2);
+    assert_ne!(formats[0].0, formats[1].0);
+
+    let mut buf = vec![];
+    Event::Boot.write(&mut buf);
+    assert_eq!(buf, id(formats, "Boot"));
+
+    let mut buf = vec![];
+    Event::Login { user: 1, pin: 1234 }.write(&mut buf);
+    let mut expected = id(formats, "Login {{ user: {}, pin: [REDACTED] }}").to_vec();
+    expected.extend([1, 0, 0, 0]);
+    assert_eq!(buf, expected);
+
+    let status = Status { code: 10 };
+    assert_eq!(Status::COMPACT_FORMATS[0].1, "Status {{ code: {} ({:#x}) }}");
+    let mut buf = vec![];
+    status.write(&mut buf);
+    let mut expected = Status::COMPACT_FORMATS[0].0.to_le_bytes().to_vec();
+    expected.extend([10, 10]);
+    assert_eq!(buf, expected);
+    assert_eq!(format!("{:?}", status), "Status { code: 10 (0xa) }");
+}
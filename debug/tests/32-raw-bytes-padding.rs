@@ -0,0 +1,35 @@
+// Padding bytes are uninitialized, so #[debug(raw_bytes)] must never read
+// them. Types whose fields don't cover every byte are rejected: structs with
+// padding between or after their fields, structs with a raised alignment, and
+// unions whose fields have different sizes.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug, Clone, Copy)]
+#[debug(raw_bytes)]
+#[repr(C)]
+pub struct Padded {
+    a: u8,
+    b: u32,
+}
+
+#[derive(CustomDebug, Clone, Copy)]
+#[debug(raw_bytes)]
+#[repr(C, align(8))]
+pub struct Aligned {
+    a: u32,
+}
+
+#[derive(CustomDebug, Clone, Copy)]
+#[debug(raw_bytes)]
+#[repr(C)]
+pub union Mixed {
+    a: u8,
+    b: u32,
+}
+
+fn main() {
+    let _ = format!("{:?}", Padded { a: 1, b: 2 });
+    let _ = format!("{:?}", Aligned { a: 1 });
+    let _ = format!("{:?}", Mixed { a: 1 });
+}
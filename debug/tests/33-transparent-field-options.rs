@@ -0,0 +1,27 @@
+// The field of a #[debug(transparent)] struct keeps the options that change how
+// its value is printed, so a redacted newtype stays redacted. Options about the
+// field's place in the struct, `skip`, `skip_if` and `rename`, are rejected
+// since a transparent struct prints no field names or list of fields.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Token(#[debug(redact)] &'static str);
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Flags {
+    #[debug = "{:#06b}"]
+    bits: u8,
+}
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Buffer(#[debug(max_len = 2)] Vec<u8>);
+
+fn main() {
+    assert_eq!(format!("{:?}", Token("hunter2")), "[REDACTED]");
+    assert_eq!(format!("{:?}", Flags { bits: 5 }), "0b0101");
+    assert_eq!(format!("{:?}", Buffer(vec![1, 2, 3])), "[1, 2, ..; 1 more]");
+}
@@ -0,0 +1,10 @@
+// A transparent struct prints nothing but its only field, so that field can't
+// be skipped.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Token(#[debug(skip)] &'static str);
+
+fn main() {}
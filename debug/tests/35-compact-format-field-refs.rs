@@ -0,0 +1,20 @@
+// CustomFormat writes the fields in order and its table keeps one positional
+// placeholder per write, so format strings referring to other fields, which
+// CustomDebug accepts, would not match what is written and are rejected.
+
+use derive_debug::CustomFormat;
+
+pub trait Write {}
+
+pub trait CompactFormat {
+    fn write(&self, w: &mut impl Write);
+}
+
+#[derive(CustomFormat)]
+pub struct Measurement {
+    #[debug = "{self.value} {unit}"]
+    value: u32,
+    unit: u32,
+}
+
+fn main() {}
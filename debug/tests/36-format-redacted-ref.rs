@@ -0,0 +1,14 @@
+// A #[debug = "..."] format string may not refer to a field that is skipped or
+// redacted, which would print the very value the attribute hides.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Credentials {
+    #[debug = "{} / {password}"]
+    user: String,
+    #[debug(redact)]
+    password: String,
+}
+
+fn main() {}
@@ -9,4 +9,32 @@ fn tests() {
     t.pass("tests/06-bound-trouble.rs");
     t.pass("tests/07-associated-type.rs");
     t.pass("tests/08-escape-hatch.rs");
+    t.pass("tests/09-enum-tuple-unit.rs");
+    t.pass("tests/10-skip-redact.rs");
+    t.compile_fail("tests/11-redact-with-format.rs");
+    t.pass("tests/12-with-formatter.rs");
+    t.compile_fail("tests/13-field-attr-errors.rs");
+    t.compile_fail("tests/14-bound-errors.rs");
+    t.pass("tests/15-multiple-bounds.rs");
+    t.pass("tests/16-nested-bounds.rs");
+    t.pass("tests/17-display.rs");
+    t.compile_fail("tests/18-display-unknown-field.rs");
+    t.pass("tests/19-rename-compact.rs");
+    t.pass("tests/20-skip-none.rs");
+    t.pass("tests/21-format-field-refs.rs");
+    t.compile_fail("tests/22-format-unknown-field.rs");
+    t.pass("tests/23-unions.rs");
+    t.compile_fail("tests/24-raw-bytes-repr.rs");
+    t.pass("tests/25-no-std.rs");
+    t.pass("tests/26-attribute-order.rs");
+    t.compile_fail("tests/27-attribute-conflicts.rs");
+    t.pass("tests/28-max-len-ptr.rs");
+    t.pass("tests/29-discriminant.rs");
+    t.compile_fail("tests/30-discriminant-fields.rs");
+    t.pass("tests/31-compact-format.rs");
+    t.compile_fail("tests/32-raw-bytes-padding.rs");
+    t.pass("tests/33-transparent-field-options.rs");
+    t.compile_fail("tests/34-transparent-skip.rs");
+    t.compile_fail("tests/35-compact-format-field-refs.rs");
+    t.compile_fail("tests/36-format-redacted-ref.rs");
 }
@@ -1,38 +1,379 @@
 use proc_macro::TokenStream;
+use proc_macro2::TokenTree;
 
 #[derive(Debug)]
 struct Seq {
-    name: syn::Ident,
-    range: std::ops::Range<u64>,
+    // `N`, or `I, J` for `(I, J) in 0..4, 0..4`
+    names: Vec<syn::Ident>,
+    // one range per name, iterated as their product
+    ranges: Vec<SeqRange>,
     body: proc_macro2::TokenStream,
-    repeated_section: bool,
+}
+
+#[derive(Debug)]
+struct SeqRange {
+    // the values are `start + i * step` for `i` in `0..len`, computed when
+    // needed so a huge range with a large step stays cheap
+    start: i128,
+    step: i128,
+    len: u128,
+    // suffix and radix of the bounds, reused for the substituted literals
+    suffix: String,
+    radix: Radix,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Radix {
+    Binary,
+    Octal,
+    Decimal,
+    Hex,
 }
 
 impl syn::parse::Parse for Seq {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        let name: syn::Ident = input.parse()?;
-        let _: syn::Token![in] = input.parse()?;
-        let start: u64 = input.parse::<syn::LitInt>()?.base10_parse()?;
-        let _: syn::Token![..] = input.parse()?;
-        let end: u64 = input.parse::<syn::LitInt>()?.base10_parse()?;
+        let names: Vec<syn::Ident> = if input.peek(syn::token::Paren) {
+            let content;
+            syn::parenthesized!(content in input);
+            syn::punctuated::Punctuated::<syn::Ident, syn::Token![,]>::parse_terminated(&content)?
+                .into_iter()
+                .collect()
+        } else {
+            vec![input.parse()?]
+        };
+        let in_token: syn::Token![in] = input.parse()?;
+        let mut ranges = vec![input.parse::<SeqRange>()?];
+        while input.peek(syn::Token![,]) {
+            let _: syn::Token![,] = input.parse()?;
+            ranges.push(input.parse()?);
+        }
+        if names.len() != ranges.len() {
+            return Err(syn::Error::new_spanned(
+                in_token,
+                format!(
+                    "expected {} ranges, one per name, found {}",
+                    names.len(),
+                    ranges.len()
+                ),
+            ));
+        }
         let content;
         syn::braced!(content in input);
         let body: proc_macro2::TokenStream = content.parse()?;
-        dbg!(&body);
 
         Ok(Seq {
-            name,
-            range: std::ops::Range { start, end },
-            body: proc_macro2::TokenStream::new(),
-            repeated_section: true,
+            names,
+            ranges,
+            body,
         })
     }
 }
 
+// `start..end` or `start..=end`, optionally parenthesized and followed by
+// `.step_by(n)` and `.rev()` calls
+impl syn::parse::Parse for SeqRange {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        if !input.peek(syn::token::Paren) {
+            return parse_bounds(input);
+        }
+        let content;
+        syn::parenthesized!(content in input);
+        let mut range = parse_bounds(&content)?;
+        while input.peek(syn::Token![.]) {
+            let _: syn::Token![.] = input.parse()?;
+            let method: syn::Ident = input.parse()?;
+            let args;
+            syn::parenthesized!(args in input);
+            if method == "step_by" {
+                let step: syn::LitInt = args.parse()?;
+                let step: u128 = step.base10_parse()?;
+                if step == 0 {
+                    return Err(args.error("step must be greater than zero"));
+                }
+                range.len = range.len.div_ceil(step);
+                if range.len > 1 {
+                    range.step = i128::try_from(step)
+                        .ok()
+                        .and_then(|step| range.step.checked_mul(step))
+                        .ok_or_else(|| args.error("step is too large"))?;
+                }
+            } else if method == "rev" && args.is_empty() {
+                if range.len > 0 {
+                    range.start = range.value(range.len - 1);
+                    range.step = -range.step;
+                }
+            } else {
+                return Err(syn::Error::new_spanned(
+                    method,
+                    "expected `step_by(n)` or `rev()`",
+                ));
+            }
+        }
+        Ok(range)
+    }
+}
+
+fn parse_bounds(input: syn::parse::ParseStream) -> syn::Result<SeqRange> {
+    let (start, start_lit) = parse_bound(input)?;
+    let inclusive = input.peek(syn::Token![..=]);
+    if inclusive {
+        let _: syn::Token![..=] = input.parse()?;
+    } else {
+        let _: syn::Token![..] = input.parse()?;
+    }
+    let (end, end_lit) = parse_bound(input)?;
+    let end = if inclusive { end + 1 } else { end };
+
+    // a suffix on either bound applies to both, `0..16u8` counts in `u8`
+    let suffix = match (start_lit.suffix(), end_lit.suffix()) {
+        ("", suffix) => suffix,
+        (suffix, "") => suffix,
+        (start, end) if start == end => start,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                end_lit,
+                "range bounds have different suffixes",
+            ))
+        }
+    };
+
+    Ok(SeqRange {
+        start,
+        step: 1,
+        len: end.checked_sub(start).map_or(0, |len| len.max(0) as u128),
+        suffix: suffix.to_owned(),
+        radix: get_radix(&start_lit),
+    })
+}
+
+// integer literal with an optional leading `-`
+fn parse_bound(input: syn::parse::ParseStream) -> syn::Result<(i128, syn::LitInt)> {
+    let negative = input.peek(syn::Token![-]);
+    if negative {
+        let _: syn::Token![-] = input.parse()?;
+    }
+    let lit: syn::LitInt = input.parse()?;
+    let value: i128 = lit.base10_parse()?;
+    Ok((if negative { -value } else { value }, lit))
+}
+
+fn get_radix(lit: &syn::LitInt) -> Radix {
+    let text = lit.to_string();
+    if text.starts_with("0x") {
+        Radix::Hex
+    } else if text.starts_with("0o") {
+        Radix::Octal
+    } else if text.starts_with("0b") {
+        Radix::Binary
+    } else {
+        Radix::Decimal
+    }
+}
+
+impl SeqRange {
+    fn value(&self, i: u128) -> i128 {
+        self.start + self.step * i as i128
+    }
+
+    fn values(&self) -> impl Iterator<Item = i128> + '_ {
+        (0..self.len).map(|i| self.value(i))
+    }
+
+    // `n` written with the radix and suffix of the range bounds, a negative
+    // value as a `-` followed by the literal
+    fn literal(&self, n: i128, span: proc_macro2::Span) -> TokenTree {
+        let magnitude = n.unsigned_abs();
+        let digits = match self.radix {
+            Radix::Binary => format!("{:#b}", magnitude),
+            Radix::Octal => format!("{:#o}", magnitude),
+            Radix::Decimal => magnitude.to_string(),
+            Radix::Hex => format!("{:#x}", magnitude),
+        };
+        let mut lit: proc_macro2::Literal = format!("{}{}", digits, self.suffix)
+            .parse()
+            .expect("formatted integer is a valid literal");
+        lit.set_span(span);
+        if n < 0 {
+            let mut minus = proc_macro2::Punct::new('-', proc_macro2::Spacing::Alone);
+            minus.set_span(span);
+            let mut group = proc_macro2::Group::new(
+                proc_macro2::Delimiter::None,
+                [TokenTree::Punct(minus), TokenTree::Literal(lit)]
+                    .into_iter()
+                    .collect(),
+            );
+            group.set_span(span);
+            TokenTree::Group(group)
+        } else {
+            TokenTree::Literal(lit)
+        }
+    }
+}
+
+impl Seq {
+    // every combination of values, one per range, with the last range varying fastest
+    fn iterations(&self) -> Vec<Vec<i128>> {
+        self.ranges.iter().fold(vec![vec![]], |iterations, range| {
+            iterations
+                .iter()
+                .flat_map(|prefix| {
+                    range.values().map(move |n| {
+                        let mut values = prefix.clone();
+                        values.push(n);
+                        values
+                    })
+                })
+                .collect()
+        })
+    }
+
+    fn expand_body(&self) -> syn::Result<proc_macro2::TokenStream> {
+        if has_repeated_section(self.body.clone()) {
+            self.expand_repeated_sections(self.body.clone())
+        } else {
+            self.iterations()
+                .iter()
+                .map(|values| self.expand(self.body.clone(), values))
+                .collect()
+        }
+    }
+
+    // Copy of `tokens` with every `#(...)*` replaced by its contents expanded
+    // once per iteration, leaving everything else as it is.
+    fn expand_repeated_sections(
+        &self,
+        tokens: proc_macro2::TokenStream,
+    ) -> syn::Result<proc_macro2::TokenStream> {
+        let tokens: Vec<_> = tokens.into_iter().collect();
+        let mut expanded = proc_macro2::TokenStream::new();
+        let mut i = 0;
+        while i < tokens.len() {
+            if let Some(section) = repeated_section(&tokens[i..]) {
+                for values in self.iterations() {
+                    expanded.extend(self.expand(section.stream(), &values)?);
+                }
+                i += 3;
+                continue;
+            }
+            expanded.extend(std::iter::once(match &tokens[i] {
+                TokenTree::Group(group) => {
+                    let stream = self.expand_repeated_sections(group.stream())?;
+                    let mut new = proc_macro2::Group::new(group.delimiter(), stream);
+                    new.set_span(group.span());
+                    TokenTree::Group(new)
+                }
+                tt => tt.clone(),
+            }));
+            i += 1;
+        }
+        Ok(expanded)
+    }
+
+    // Copy of `tokens` with every `N` replaced by its value, a literal which
+    // keeps the span of the `N` it replaces so errors point into the body, and
+    // every `prefix~N~suffix` pasted into a single identifier.
+    fn expand(
+        &self,
+        tokens: proc_macro2::TokenStream,
+        values: &[i128],
+    ) -> syn::Result<proc_macro2::TokenStream> {
+        let tokens: Vec<_> = tokens.into_iter().collect();
+        let mut expanded = proc_macro2::TokenStream::new();
+        let mut i = 0;
+        while i < tokens.len() {
+            let tt = match &tokens[i] {
+                TokenTree::Group(group) => {
+                    let mut new = proc_macro2::Group::new(
+                        group.delimiter(),
+                        self.expand(group.stream(), values)?,
+                    );
+                    new.set_span(group.span());
+                    TokenTree::Group(new)
+                }
+                TokenTree::Ident(ident) => {
+                    // `a~b~c` is an identifier followed by pairs of `~` and identifier
+                    let mut pasted: Option<String> = None;
+                    while let (Some(TokenTree::Punct(tilde)), Some(TokenTree::Ident(next))) =
+                        (tokens.get(i + 1), tokens.get(i + 2))
+                    {
+                        if tilde.as_char() != '~' {
+                            break;
+                        }
+                        pasted
+                            .get_or_insert_with(|| self.paste_segment(ident, values))
+                            .push_str(&self.paste_segment(next, values));
+                        i += 2;
+                    }
+                    let index = self.names.iter().position(|name| ident == name);
+                    match (pasted, index) {
+                        (None, Some(index)) => {
+                            self.ranges[index].literal(values[index], ident.span())
+                        }
+                        (None, None) => TokenTree::Ident(ident.clone()),
+                        (Some(pasted), _) if is_ident(&pasted) => {
+                            TokenTree::Ident(proc_macro2::Ident::new(&pasted, ident.span()))
+                        }
+                        (Some(pasted), _) => {
+                            return Err(syn::Error::new(
+                                ident.span(),
+                                format!("`{}` is not a valid identifier", pasted),
+                            ))
+                        }
+                    }
+                }
+                tt => tt.clone(),
+            };
+            expanded.extend(std::iter::once(tt));
+            i += 1;
+        }
+        Ok(expanded)
+    }
+
+    // text one identifier contributes to a pasted identifier
+    fn paste_segment(&self, ident: &proc_macro2::Ident, values: &[i128]) -> String {
+        match self.names.iter().position(|name| ident == name) {
+            Some(index) => values[index].to_string(),
+            None => ident.to_string(),
+        }
+    }
+}
+
+// `#(...)*` at the start of `tokens`, returning the parenthesized group
+fn repeated_section(tokens: &[TokenTree]) -> Option<&proc_macro2::Group> {
+    match tokens {
+        [TokenTree::Punct(pound), TokenTree::Group(group), TokenTree::Punct(star), ..]
+            if pound.as_char() == '#'
+                && group.delimiter() == proc_macro2::Delimiter::Parenthesis
+                && star.as_char() == '*' =>
+        {
+            Some(group)
+        }
+        _ => None,
+    }
+}
+
+// whether the body contains `#(...)*`, which then is the only part repeated
+fn has_repeated_section(tokens: proc_macro2::TokenStream) -> bool {
+    let tokens: Vec<_> = tokens.into_iter().collect();
+    (0..tokens.len()).any(|i| match &tokens[i] {
+        _ if repeated_section(&tokens[i..]).is_some() => true,
+        TokenTree::Group(group) => has_repeated_section(group.stream()),
+        _ => false,
+    })
+}
+
+fn is_ident(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
 #[proc_macro]
 pub fn seq(input: TokenStream) -> TokenStream {
     let input: Seq = syn::parse_macro_input!(input as Seq);
-    dbg!(input);
 
-    quote::quote! {}.into()
+    input
+        .expand_body()
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
 }
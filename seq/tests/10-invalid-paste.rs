@@ -0,0 +1,11 @@
+// Pasting has to produce a valid identifier. Starting with the number, as in
+// `N~th`, gives `0th`, which is reported at the first pasted token instead of
+// leaving the compiler to fail on an unparseable expansion.
+
+use seq::seq;
+
+seq!(N in 0..1 {
+    fn N~th() {}
+});
+
+fn main() {}
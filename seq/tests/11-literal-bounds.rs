@@ -0,0 +1,31 @@
+// The bounds may be negative, and the substituted literals keep the suffix and
+// radix the bounds were written with. A suffix on either bound applies to the
+// whole range, so `0..4u8` yields `0u8`, `1u8`, ... and the values can be used
+// where a `u8` is expected without casts.
+
+use seq::seq;
+
+const fn byte(b: u8) -> u8 {
+    b
+}
+
+seq!(N in 0..4u8 {
+    const BYTES: [u8; 4] = [#(byte(N),)*];
+});
+
+seq!(N in 0x0e..=0x11 {
+    const HEX: [&str; 4] = [#(stringify!(N),)*];
+});
+
+seq!(N in -2..2 {
+    const SIGNED: [i32; 4] = [#(N * 10,)*];
+});
+
+fn main() {
+    assert_eq!(BYTES, [0, 1, 2, 3]);
+    assert_eq!(HEX, ["0xe", "0xf", "0x10", "0x11"]);
+    assert_eq!(SIGNED, [-20, -10, 0, 10]);
+
+    let suffixed = seq!(N in 7u16..8u16 { stringify!(N) });
+    assert_eq!(suffixed, "7u16");
+}
@@ -0,0 +1,45 @@
+// Ranges can be adjusted the way iterators are, with `.step_by(n)` and
+// `.rev()` on a parenthesized range, and several ranges can be iterated at once
+// as their product, binding one name each:
+//
+//     seq!((I, J) in 0..2, 0..3 { ... })
+//
+// goes through (0, 0), (0, 1), (0, 2), (1, 0), ... with the last range varying
+// fastest, like nested loops would.
+//
+// Only the values iterated over are computed, so a huge range with a large
+// step is as cheap as a short one.
+
+use seq::seq;
+
+seq!(N in (0..16).step_by(4) {
+    const OFFSETS: [u32; 4] = [#(N,)*];
+});
+
+seq!(N in (0..=3).rev() {
+    const COUNTDOWN: [u32; 4] = [#(N,)*];
+});
+
+seq!(N in (0..8).step_by(3).rev() {
+    const BOTH: [u32; 3] = [#(N,)*];
+});
+
+seq!(N in (0..0x1_0000_0000u64).step_by(0x4000_0000).rev() {
+    const QUARTERS: [u64; 4] = [#(N,)*];
+});
+
+seq!((I, J) in 0..2, 0..3 {
+    const TABLE: [(u32, u32); 6] = [#((I, J),)*];
+    #(
+        const CELL_~I~_~J: u32 = I * 10 + J;
+    )*
+});
+
+fn main() {
+    assert_eq!(OFFSETS, [0, 4, 8, 12]);
+    assert_eq!(COUNTDOWN, [3, 2, 1, 0]);
+    assert_eq!(BOTH, [6, 3, 0]);
+    assert_eq!(QUARTERS, [0xc000_0000, 0x8000_0000, 0x4000_0000, 0]);
+    assert_eq!(TABLE, [(0, 0), (0, 1), (0, 2), (1, 0), (1, 1), (1, 2)]);
+    assert_eq!(CELL_1_2, 12);
+}
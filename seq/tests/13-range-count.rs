@@ -0,0 +1,7 @@
+// Every name needs a range of its own.
+
+use seq::seq;
+
+seq!((I, J) in 0..4 {});
+
+fn main() {}